@@ -8,7 +8,7 @@
 //! 
 //! # Usage/Examples
 //! 
-//! See [`binop`]
+//! See [`binop`], [`assignop`], and [`unop`]; custom operator traits can be defined with [`op_trait`], the operators of a newtype can be forwarded to its field with [`Ops`], and those of any struct can be applied field by field with [`FieldwiseOps`]
 //! 
 //! # Operator Overloading Basics
 //! 
@@ -37,12 +37,16 @@
 
 
 
-pub use xops_macros::binop;
+pub use xops_macros::{assignop, binop, op_trait, unop, FieldwiseOps, Ops};
 
 #[cfg(test)]
+#[allow(clippy::op_ref)]
 mod tests {
+    use std::borrow::Cow;
     use std::fmt;
-    use std::ops::Mul;
+    use std::ops::{Add, Div, Mul, Neg, Shr, Sub, SubAssign};
+    use std::rc::Rc;
+    use std::sync::Arc;
     use xops_macros::*;
 
     #[derive(Clone)]
@@ -126,4 +130,1134 @@ mod tests {
         let x = std::any::type_name::<Fish<String>>();
         dbg!(x);
     }
+
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    struct Cat(i32);
+
+    #[binop(refs_clone, derefs)]
+    impl Mul<Cat> for &Dog {
+        type Output = Cat;
+
+        fn mul(self, rhs: Cat) -> Cat {
+            Cat(self.0 * rhs.0)
+        }
+    }
+
+    #[test]
+    fn all_refs_test() {
+        assert_eq!( Dog(3) *  Cat(5), Cat(15));
+        assert_eq!(&Dog(3) *  Cat(5), Cat(15));
+        assert_eq!( Dog(3) * &Cat(5), Cat(15));
+        assert_eq!(&Dog(3) * &Cat(5), Cat(15));
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    struct Vec2<T>(T, T);
+
+    #[binop(commute, refs_copy)]
+    impl Mul<i32> for Vec2<i32> {
+        type Output = Vec2<i32>;
+
+        fn mul(self, rhs: i32) -> Vec2<i32> {
+            Vec2(self.0 * rhs, self.1 * rhs)
+        }
+    }
+
+    #[binop(refs_copy, derefs, assign, sum(zero = "Vec2(T::default(), T::default())"))]
+    impl<T> Add<&Vec2<T>> for Vec2<T>
+    where
+        T: Copy + Default + Add<Output = T>,
+    {
+        type Output = Vec2<T>;
+
+        fn add(self, rhs: &Vec2<T>) -> Vec2<T> {
+            Vec2(self.0 + rhs.0, self.1 + rhs.1)
+        }
+    }
+
+    #[test]
+    fn refs_copy_test() {
+        let v = Vec2(1, 2);
+
+        assert_eq!( v *  3, Vec2(3, 6));
+        assert_eq!(&v *  3, Vec2(3, 6));
+        assert_eq!( v * &3, Vec2(3, 6));
+        assert_eq!(&v * &3, Vec2(3, 6));
+        assert_eq!( 3 * &v, Vec2(3, 6));
+        assert_eq!(&3 * &v, Vec2(3, 6));
+
+        assert_eq!( v +  v, Vec2(2, 4));
+        assert_eq!(&v +  v, Vec2(2, 4));
+        assert_eq!( v + &v, Vec2(2, 4));
+        assert_eq!(&v + &v, Vec2(2, 4));
+    }
+
+    #[derive(Clone, Default, Debug, PartialEq, Eq)]
+    struct Bag(Vec<i32>);
+
+    #[binop(refs_clone, assign(take))]
+    impl Mul<i32> for Bag {
+        type Output = Bag;
+
+        fn mul(self, rhs: i32) -> Bag {
+            Bag(self.0.into_iter().map(|x| x * rhs).collect())
+        }
+    }
+
+    #[test]
+    fn assign_test() {
+        let mut v = Vec2(1, 2);
+        v += Vec2(1, 1);
+        v += &Vec2(1, 1);
+        assert_eq!(v, Vec2(3, 4));
+
+        let mut bag = Bag(vec![1, 2]);
+        bag *= 2;
+        bag *= &3;
+        assert_eq!(bag, Bag(vec![6, 12]));
+    }
+
+    #[assignop]
+    impl SubAssign<i32> for Bag {
+        fn sub_assign(&mut self, rhs: i32) {
+            self.0.iter_mut().for_each(|x| *x -= rhs);
+        }
+    }
+
+    #[test]
+    fn assignop_test() {
+        let bag = Bag(vec![3, 4]);
+
+        assert_eq!(&bag - 1, Bag(vec![2, 3]));
+        assert_eq!( bag - 1, Bag(vec![2, 3]));
+    }
+
+    #[unop(refs_clone)]
+    impl Neg for Bag {
+        type Output = Bag;
+
+        fn neg(self) -> Bag {
+            Bag(self.0.into_iter().map(|x| -x).collect())
+        }
+    }
+
+    #[unop(derefs)]
+    impl<T> Neg for &Vec2<T>
+    where
+        T: Copy + Neg<Output = T>,
+    {
+        type Output = Vec2<T>;
+
+        fn neg(self) -> Vec2<T> {
+            Vec2(-self.0, -self.1)
+        }
+    }
+
+    #[test]
+    fn unop_test() {
+        let bag = Bag(vec![1, -2]);
+        assert_eq!(-&bag, Bag(vec![-1, 2]));
+        assert_eq!(-bag, Bag(vec![-1, 2]));
+
+        let v = Vec2(1, -2);
+        assert_eq!(-&v, Vec2(-1, 2));
+        assert_eq!(-v, Vec2(-1, 2));
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Stack<T>(Vec<T>);
+
+    #[binop(refs_clone)]
+    impl<T> Add for Stack<T> {
+        type Output = Stack<T>;
+
+        fn add(self, rhs: Stack<T>) -> Stack<T> {
+            Stack(self.0.into_iter().chain(rhs.0).collect())
+        }
+    }
+
+    #[unop(refs_clone)]
+    impl<T> Neg for Stack<T> {
+        type Output = Stack<T>;
+
+        fn neg(self) -> Stack<T> {
+            Stack(self.0.into_iter().rev().collect())
+        }
+    }
+
+    #[test]
+    fn generic_refs_clone_test() {
+        let (s, t) = (Stack(vec![1, 2]), Stack(vec![3]));
+
+        assert_eq!(&s + &t, Stack(vec![1, 2, 3]));
+        assert_eq!(&s + t.clone(), Stack(vec![1, 2, 3]));
+        assert_eq!(s.clone() + &t, Stack(vec![1, 2, 3]));
+        assert_eq!(-&s, Stack(vec![2, 1]));
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Scale(f64);
+
+    #[binop(refs_copy, product(one = "Scale(1.0)"))]
+    impl Mul for Scale {
+        type Output = Scale;
+
+        fn mul(self, rhs: Scale) -> Scale {
+            Scale(self.0 * rhs.0)
+        }
+    }
+
+    #[test]
+    fn sum_product_test() {
+        let vs = vec![Vec2(1, 2), Vec2(3, 4), Vec2(5, 6)];
+
+        assert_eq!(vs.iter().sum::<Vec2<i32>>(), Vec2(9, 12));
+        assert_eq!(vs.into_iter().sum::<Vec2<i32>>(), Vec2(9, 12));
+
+        let scales = [Scale(2.0), Scale(0.5), Scale(3.0)];
+        assert_eq!(scales.iter().product::<Scale>(), Scale(3.0));
+        assert_eq!(scales.iter().copied().product::<Scale>(), Scale(3.0));
+    }
+
+    #[derive(Clone, Default, Debug, PartialEq)]
+    struct Series(Vec<i32>);
+
+    #[binop(derefs, assign(take), sum(zero = "Series(Vec::new())"))]
+    impl Add<&Series> for &Series {
+        type Output = Series;
+
+        fn add(self, rhs: &Series) -> Series {
+            let len = self.0.len().max(rhs.0.len());
+            let coeff = |s: &Series, i| s.0.get(i).copied().unwrap_or(0);
+            Series((0..len).map(|i| coeff(self, i) + coeff(rhs, i)).collect())
+        }
+    }
+
+    #[test]
+    fn ref_base_assign_sum_test() {
+        let (s, t) = (Series(vec![1, 2]), Series(vec![3]));
+
+        let mut u = s.clone();
+        u += &t;
+        u += t.clone();
+        assert_eq!(u, Series(vec![7, 2]));
+
+        let series = vec![s, t, Series(vec![0, 0, 5])];
+        assert_eq!(series.iter().sum::<Series>(), Series(vec![4, 2, 5]));
+        assert_eq!(series.into_iter().sum::<Series>(), Series(vec![4, 2, 5]));
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Angle(f64);
+
+    #[unop(refs_copy)]
+    impl Neg for Angle {
+        type Output = Angle;
+
+        fn neg(self) -> Angle {
+            Angle(-self.0)
+        }
+    }
+
+    #[binop(derive_sub, refs_copy, assign)]
+    impl Add for Angle {
+        type Output = Angle;
+
+        fn add(self, rhs: Angle) -> Angle {
+            Angle(self.0 + rhs.0)
+        }
+    }
+
+    #[binop(commute, derive_div(recip = "f64::recip"))]
+    impl Mul<f64> for Angle {
+        type Output = Angle;
+
+        fn mul(self, rhs: f64) -> Angle {
+            Angle(self.0 * rhs)
+        }
+    }
+
+    #[test]
+    fn derive_sub_div_test() {
+        let mut a = Angle(3.0);
+
+        assert_eq!( a -  Angle(1.0), Angle(2.0));
+        assert_eq!(&a - &Angle(1.0), Angle(2.0));
+        a -= Angle(0.5);
+        assert_eq!(a, Angle(2.5));
+
+        assert_eq!(a / 2.0, Angle(1.25));
+        assert_eq!(2.0 * a, Angle(5.0));
+    }
+
+    #[binop(anticommute)]
+    impl Sub<f64> for Angle {
+        type Output = Angle;
+
+        fn sub(self, rhs: f64) -> Angle {
+            Angle(self.0 - rhs)
+        }
+    }
+
+    fn div_angle(lhs: i32, rhs: Angle) -> Angle {
+        Angle(f64::from(lhs) / rhs.0)
+    }
+
+    #[binop(commute_with = "div_angle")]
+    impl Div<i32> for Angle {
+        type Output = Angle;
+
+        fn div(self, rhs: i32) -> Angle {
+            Angle(self.0 / f64::from(rhs))
+        }
+    }
+
+    #[test]
+    fn anticommute_test() {
+        assert_eq!(Angle(3.0) - 1.0, Angle(2.0));
+        assert_eq!(1.0 - Angle(3.0), Angle(-2.0));
+
+        assert_eq!(Angle(3.0) / 2, Angle(1.5));
+        assert_eq!(3 / Angle(2.0), Angle(1.5));
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Meters(f64);
+
+    #[binop(refs_clone, assign(replace = "Meters(0.0)"))]
+    impl Add<Self> for Meters
+    where
+        Self: Clone,
+    {
+        type Output = Self;
+
+        fn add(self, rhs: Self) -> Self::Output {
+            Self(self.0 + rhs.0)
+        }
+    }
+
+    #[binop(commute, refs_clone)]
+    impl Mul<f64> for Meters {
+        type Output = Self;
+
+        fn mul(self, rhs: f64) -> Self {
+            Self(self.0 * rhs)
+        }
+    }
+
+    #[unop(refs_clone)]
+    impl Neg for Meters {
+        type Output = Self;
+
+        fn neg(self) -> Self {
+            Self(-self.0)
+        }
+    }
+
+    #[test]
+    fn resolve_self_test() {
+        let mut m = Meters(1.5);
+
+        assert_eq!(&m +  Meters(1.0), Meters(2.5));
+        assert_eq!(&m + &Meters(1.0), Meters(2.5));
+        m += &Meters(0.5);
+        assert_eq!(m, Meters(2.0));
+
+        assert_eq!(  3.0 * &m, Meters(6.0));
+        assert_eq!(&3.0 *  m.clone(), Meters(6.0));
+        assert_eq!(-&m, Meters(-2.0));
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Speed(f64);
+
+    #[binop(commute, refs_copy, rhs_types(f32, i32, u8))]
+    impl<S: Into<f64>> Mul<S> for Speed {
+        type Output = Speed;
+
+        fn mul(self, rhs: S) -> Speed {
+            Speed(self.0 * Into::<f64>::into(rhs))
+        }
+    }
+
+    #[binop(lhs_types(f32, i32))]
+    impl Sub<Speed> for Scalar {
+        type Output = Speed;
+
+        fn sub(self, rhs: Speed) -> Speed {
+            Speed(f64::from(self) - rhs.0)
+        }
+    }
+
+    #[test]
+    fn types_test() {
+        let v = Speed(1.5);
+
+        assert_eq!(v * 2.0f32, Speed(3.0));
+        assert_eq!(&v * 2i32, Speed(3.0));
+        assert_eq!(2u8 * &v, Speed(3.0));
+        assert_eq!(&2i32 * &v, Speed(3.0));
+
+        assert_eq!(2.0f32 - v, Speed(0.5));
+        assert_eq!(2i32 - v, Speed(0.5));
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Grams(i64);
+
+    impl From<u8> for Grams {
+        fn from(n: u8) -> Self {
+            Grams(n.into())
+        }
+    }
+
+    impl From<i32> for Grams {
+        fn from(n: i32) -> Self {
+            Grams(n.into())
+        }
+    }
+
+    #[binop(commute, refs_copy, rhs_from(u8, i32))]
+    impl Add for Grams {
+        type Output = Grams;
+
+        fn add(self, rhs: Grams) -> Grams {
+            Grams(self.0 + rhs.0)
+        }
+    }
+
+    #[binop(lhs_from(i32))]
+    impl Sub for Grams {
+        type Output = Grams;
+
+        fn sub(self, rhs: Grams) -> Grams {
+            Grams(self.0 - rhs.0)
+        }
+    }
+
+    #[test]
+    fn from_test() {
+        let g = Grams(5);
+
+        assert_eq!(g + 3u8, Grams(8));
+        assert_eq!(&g + -3i32, Grams(2));
+        assert_eq!(g + &7i32, Grams(12));
+        assert_eq!(3u8 + g, Grams(8));
+        assert_eq!(&3u8 + &g, Grams(8));
+        assert_eq!(&g + &g, Grams(10));
+        assert_eq!(9i32 - g, Grams(4));
+    }
+
+    trait Weigh<Rhs = Self> {
+        const FACTOR: i64;
+        type Output;
+        fn weigh(self, rhs: Rhs) -> Self::Output;
+    }
+
+    #[binop(refs_copy)]
+    impl Weigh<i64> for Grams {
+        /// scales by `rhs` and the factor
+        #[inline]
+        fn weigh(self, rhs: i64) -> Self::Output
+        where
+            Self: Copy,
+        {
+            Grams(self.0 * rhs * <Self as Weigh<i64>>::FACTOR)
+        }
+
+        const FACTOR: i64 = 10;
+
+        type Output = Grams;
+    }
+
+    #[test]
+    fn item_roles_test() {
+        let g = Grams(2);
+
+        assert_eq!(g.weigh(3), Grams(60));
+        assert_eq!((&g).weigh(&3), Grams(60));
+        assert_eq!(<&Grams as Weigh<i64>>::FACTOR, 10);
+    }
+
+    trait Halve {
+        const DIVISOR: i64;
+        type Output;
+        fn halve(self) -> Self::Output;
+    }
+
+    #[unop(refs_copy)]
+    impl Halve for Grams {
+        #[inline]
+        fn halve(self) -> Self::Output
+        where
+            Self: Copy,
+        {
+            Grams(self.0 / <Self as Halve>::DIVISOR)
+        }
+
+        const DIVISOR: i64 = 2;
+
+        type Output = Grams;
+    }
+
+    trait Clamp<Rhs = Self> {
+        type Output;
+        fn clamp(self, rhs: Rhs) -> Self::Output;
+    }
+
+    trait ClampAssign<Rhs = Self> {
+        const LIMIT: i64;
+        fn clamp_assign(&mut self, rhs: Rhs);
+    }
+
+    #[assignop]
+    impl ClampAssign<i64> for Grams {
+        const LIMIT: i64 = 1000;
+
+        /// scales by `rhs`, up to the limit
+        #[inline]
+        fn clamp_assign(&mut self, rhs: i64) {
+            self.0 = (self.0 * rhs).min(<Self as ClampAssign<i64>>::LIMIT);
+        }
+    }
+
+    #[test]
+    fn unop_assignop_item_roles_test() {
+        let g = Grams(8);
+
+        assert_eq!(g.halve(), Grams(4));
+        assert_eq!((&g).halve(), Grams(4));
+        assert_eq!(<&Grams as Halve>::DIVISOR, 2);
+
+        assert_eq!(g.clamp(3), Grams(24));
+        assert_eq!((&g).clamp(500), Grams(1000));
+    }
+
+    #[binop(commute, refs_copy, assign)]
+    impl Mul<i64> for Grams {
+        fn mul(self, rhs: i64) -> Self {
+            Grams(self.0 * rhs)
+        }
+    }
+
+    #[test]
+    fn infer_output_test() {
+        let mut g = Grams(2);
+
+        assert_eq!(g * 3, Grams(6));
+        assert_eq!(&3 * &g, Grams(6));
+        g *= 5;
+        assert_eq!(g, Grams(10));
+    }
+
+    trait Dot<Rhs, Ctx> {
+        type Scalar;
+        fn dot(&self, rhs: &Rhs) -> Self::Scalar;
+    }
+
+    struct Euclid;
+
+    #[binop(commute)]
+    impl Dot<Meters, Euclid> for Grams {
+        type Scalar = f64;
+
+        fn dot(&self, rhs: &Meters) -> f64 {
+            self.0 as f64 * rhs.0
+        }
+    }
+
+    #[test]
+    fn trait_shape_test() {
+        assert_eq!(Grams(2).dot(&Meters(1.5)), 3.0);
+        assert_eq!(Meters(1.5).dot(&Grams(2)), 3.0);
+    }
+
+    #[op_trait]
+    trait Midpoint<Rhs = Self> {
+        type Output;
+        fn midpoint(self, rhs: Rhs) -> Self::Output;
+    }
+
+    #[binop(assign(replace = "Meters(0.0)"))]
+    impl Midpoint for Meters {
+        type Output = Meters;
+
+        fn midpoint(self, rhs: Meters) -> Meters {
+            Meters((self.0 + rhs.0) / 2.0)
+        }
+    }
+
+    #[test]
+    fn op_trait_test() {
+        let (a, b) = (Meters(1.0), Meters(3.0));
+
+        assert_eq!(a.midpoint_ref(&b), Meters(2.0));
+        assert_eq!(midpoint(a.clone(), b.clone()), Meters(2.0));
+
+        let mut c = a;
+        c.midpoint_assign(b);
+        assert_eq!(c, Meters(2.0));
+    }
+
+    #[binop(assign)]
+    impl std::ops::Rem<i64> for Grams {
+        type Output = Grams;
+
+        fn rem(self, rhs: i64) -> Grams {
+            Grams(self.0 % rhs)
+        }
+    }
+
+    #[test]
+    fn qualified_path_test() {
+        let mut g = Grams(17);
+
+        assert_eq!(g % 5, Grams(2));
+        g %= 4;
+        assert_eq!(g, Grams(1));
+    }
+
+    #[binop(commute(unchecked))]
+    impl Sub<i64> for Grams {
+        type Output = u64;
+
+        fn sub(self, rhs: i64) -> u64 {
+            (self.0 - rhs).unsigned_abs()
+        }
+    }
+
+    #[test]
+    fn commute_unchecked_test() {
+        assert_eq!(Grams(2) - 5, 3);
+        assert_eq!(5_i64 - Grams(2), 3);
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct View<'a>(&'a [i32]);
+
+    #[binop(all_refs)]
+    #[allow(clippy::needless_lifetimes)]
+    impl<'a, 'b> Add<&'b View<'a>> for &'b View<'a> {
+        type Output = Vec<i32>;
+
+        fn add(self, rhs: &'b View<'a>) -> Vec<i32> {
+            self.0.iter().zip(rhs.0).map(|(a, b)| a + b).collect()
+        }
+    }
+
+    struct Row(Vec<i32>);
+
+    #[binop(derefs)]
+    impl<'a> Shr<&usize> for &'a Row {
+        type Output = View<'a>;
+
+        fn shr(self, rhs: &usize) -> View<'a> {
+            View(&self.0[*rhs..])
+        }
+    }
+
+    #[test]
+    fn borrowed_view_test() {
+        let (a, b) = ([1, 2], [3, 4]);
+        let (u, v) = (View(&a), View(&b));
+
+        assert_eq!(u + v, vec![4, 6]);
+        assert_eq!(&u + v, vec![4, 6]);
+        assert_eq!(u + &v, vec![4, 6]);
+        assert_eq!(&u + &v, vec![4, 6]);
+
+        let row = Row(vec![1, 2, 3]);
+        assert_eq!(&row >> &1, View(&[2, 3]));
+        assert_eq!(&row >> 1, View(&[2, 3]));
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Celsius(f64);
+
+    #[binop(refs_copy, mut_refs)]
+    impl Add for Celsius {
+        type Output = Celsius;
+
+        fn add(self, rhs: Celsius) -> Celsius {
+            Celsius(self.0 + rhs.0)
+        }
+    }
+
+    struct Samples(Vec<f64>);
+
+    #[binop(derefs)]
+    impl Mul<f64> for &mut Samples {
+        type Output = f64;
+
+        fn mul(self, rhs: f64) -> f64 {
+            self.0.iter_mut().for_each(|x| *x *= rhs);
+            self.0.iter().sum()
+        }
+    }
+
+    #[test]
+    fn mut_refs_test() {
+        let (mut a, mut b) = (Celsius(1.0), Celsius(2.0));
+
+        assert_eq!(&mut a + b, Celsius(3.0));
+        assert_eq!(a + &mut b, Celsius(3.0));
+        assert_eq!(&mut a + &mut b, Celsius(3.0));
+        assert_eq!(&mut a + &b, Celsius(3.0));
+        assert_eq!(&a + &mut b, Celsius(3.0));
+
+        let mut samples = Samples(vec![1.0, 2.0]);
+        assert_eq!(&mut samples * 2.0, 6.0);
+        assert_eq!(samples.0, vec![2.0, 4.0]);
+        assert_eq!(Samples(vec![1.0]) * 3.0, 3.0);
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Poly(Vec<i32>);
+
+    #[binop(boxed, rc, arc, cow)]
+    impl Add<&Poly> for Poly {
+        type Output = Poly;
+
+        fn add(self, rhs: &Poly) -> Poly {
+            Poly(self.0.iter().zip(&rhs.0).map(|(a, b)| a + b).collect())
+        }
+    }
+
+    #[binop(rc)]
+    impl Mul<i32> for Poly {
+        type Output = Poly;
+
+        fn mul(self, rhs: i32) -> Poly {
+            Poly(self.0.iter().map(|a| a * rhs).collect())
+        }
+    }
+
+    #[test]
+    fn pointers_test() {
+        let p = || Poly(vec![1, 2]);
+        let q = Poly(vec![10, 20]);
+        let sum = Poly(vec![11, 22]);
+
+        let shared = Rc::new(p());
+        let alias = Rc::clone(&shared);
+        assert_eq!(shared + &q, sum);
+        assert_eq!(*alias, p());
+        assert_eq!(Rc::new(p()) + &q, sum);
+        assert_eq!(p() + Rc::new(q.clone()), sum);
+
+        assert_eq!(Box::new(p()) + &q, sum);
+        assert_eq!(p() + Box::new(q.clone()), sum);
+        assert_eq!(Arc::new(p()) + &q, sum);
+        assert_eq!(p() + Arc::new(q.clone()), sum);
+        assert_eq!(Cow::Borrowed(&p()) + &q, sum);
+        assert_eq!(p() + Cow::Borrowed(&q), sum);
+        assert_eq!(p() + Cow::<Poly>::Owned(q.clone()), sum);
+
+        assert_eq!(p() * Rc::new(10), Poly(vec![10, 20]));
+    }
+
+    #[derive(Ops, Clone, Default, Debug, PartialEq)]
+    #[ops(Add, Sub, Mul(T), Neg, assign(take), refs)]
+    struct Wrap<T>(T);
+
+    #[derive(Ops, Clone, Copy, Debug, PartialEq)]
+    #[ops(BitAnd, BitOr, Shl(u32), Not)]
+    struct Flags {
+        bits: u8,
+    }
+
+    #[test]
+    fn ops_derive_test() {
+        let (a, b) = (Wrap(5), Wrap(3));
+        assert_eq!(&a + &b, Wrap(8));
+        assert_eq!(a.clone() - &b, Wrap(2));
+        assert_eq!(&a * 2, Wrap(10));
+        assert_eq!(-&a, Wrap(-5));
+
+        let mut c = Wrap(1.5);
+        c += Wrap(0.5);
+        c *= &2.0;
+        assert_eq!(c, Wrap(4.0));
+
+        let f = Flags { bits: 0b0110 };
+        assert_eq!(f & Flags { bits: 0b0011 }, Flags { bits: 0b0010 });
+        assert_eq!(f | Flags { bits: 0b0001 }, Flags { bits: 0b0111 });
+        assert_eq!(f << 1, Flags { bits: 0b1100 });
+        assert_eq!(!f, Flags { bits: 0b1111_1001 });
+    }
+
+    #[derive(FieldwiseOps, Clone, Debug, PartialEq)]
+    #[fieldwise(Add, Sub, Neg, refs)]
+    struct Pair<T>(T, T);
+
+    #[derive(FieldwiseOps, Clone, Copy, Debug, PartialEq)]
+    #[fieldwise(Add, Mul, assign)]
+    struct Tagged {
+        value: i32,
+        #[fieldwise(skip)]
+        tag: char,
+    }
+
+    #[derive(FieldwiseOps, Debug, PartialEq)]
+    #[fieldwise(Add)]
+    struct Origin;
+
+    #[test]
+    fn fieldwise_test() {
+        let (p, q) = (Pair(1, 2), Pair(10, 20));
+        assert_eq!(&p + &q, Pair(11, 22));
+        assert_eq!(q.clone() - &p, Pair(9, 18));
+        assert_eq!(-&p, Pair(-1, -2));
+
+        let a = Tagged { value: 3, tag: 'a' };
+        let b = Tagged { value: 4, tag: 'b' };
+        assert_eq!(a + b, Tagged { value: 7, tag: 'a' });
+        assert_eq!(b * a, Tagged { value: 12, tag: 'b' });
+
+        let mut c = a;
+        c *= b;
+        assert_eq!(c, Tagged { value: 12, tag: 'a' });
+
+        assert_eq!(Origin + Origin, Origin);
+    }
+}
+
+/// Misuses of the macros, each of which must be rejected at compile time.
+#[cfg(doctest)]
+mod compile_fail_tests {
+    /// `refs_clone` and `refs_copy` without `derefs` would derive the reference forms twice.
+    /// ```compile_fail
+    /// use std::ops::Add;
+    /// use xops::binop;
+    ///
+    /// #[derive(Clone, Copy)]
+    /// struct A(i32);
+    ///
+    /// #[binop(refs_clone, refs_copy)]
+    /// impl Add for A {
+    ///     type Output = A;
+    ///
+    ///     fn add(self, rhs: A) -> A {
+    ///         A(self.0 + rhs.0)
+    ///     }
+    /// }
+    /// ```
+    struct RefsCloneWithRefsCopy;
+
+    /// `derefs` would dereference the reference form which `refs_clone` derives from an owned
+    /// operand, deriving the owned form a second time.
+    /// ```compile_fail
+    /// use std::ops::Neg;
+    /// use xops::unop;
+    ///
+    /// #[derive(Clone)]
+    /// struct A(i32);
+    ///
+    /// #[unop(refs_clone, derefs)]
+    /// impl Neg for A {
+    ///     type Output = A;
+    ///
+    ///     fn neg(self) -> A {
+    ///         A(-self.0)
+    ///     }
+    /// }
+    /// ```
+    struct UnopRefsCloneWithDerefs;
+
+    /// The output borrows from the only reference operand, so `derefs` has no form to derive.
+    /// ```compile_fail
+    /// use std::ops::Shr;
+    /// use xops::binop;
+    ///
+    /// struct View<'a>(&'a [i32]);
+    /// struct Row(Vec<i32>);
+    ///
+    /// #[binop(derefs)]
+    /// impl<'a> Shr<usize> for &'a Row {
+    ///     type Output = View<'a>;
+    ///
+    ///     fn shr(self, rhs: usize) -> View<'a> {
+    ///         View(&self.0[rhs..])
+    ///     }
+    /// }
+    /// ```
+    struct DerefsDerivingNothing;
+
+    /// `commute` would implement the foreign `Mul` for the bare type parameter `T`.
+    /// ```compile_fail
+    /// use std::ops::Mul;
+    /// use xops::binop;
+    ///
+    /// struct Meters(f64);
+    ///
+    /// #[binop(commute)]
+    /// impl<T: Into<f64>> Mul<T> for Meters {
+    ///     type Output = Meters;
+    ///
+    ///     fn mul(self, rhs: T) -> Meters {
+    ///         Meters(self.0 * rhs.into())
+    ///     }
+    /// }
+    /// ```
+    struct CommuteOntoTypeParameter;
+
+    /// `derefs` needs a reference operand to dereference.
+    /// ```compile_fail
+    /// use std::ops::Add;
+    /// use xops::binop;
+    ///
+    /// struct A(i32);
+    ///
+    /// #[binop(derefs)]
+    /// impl Add for A {
+    ///     type Output = A;
+    ///
+    ///     fn add(self, rhs: A) -> A {
+    ///         A(self.0 + rhs.0)
+    ///     }
+    /// }
+    /// ```
+    struct DerefsWithoutReference;
+
+    /// `mut_refs` needs a shared reference operand to exchange for a mutable one.
+    /// ```compile_fail
+    /// use std::ops::Add;
+    /// use xops::binop;
+    ///
+    /// struct A(i32);
+    ///
+    /// #[binop(mut_refs)]
+    /// impl Add for A {
+    ///     type Output = A;
+    ///
+    ///     fn add(self, rhs: A) -> A {
+    ///         A(self.0 + rhs.0)
+    ///     }
+    /// }
+    /// ```
+    struct MutRefsWithoutSharedReference;
+
+    /// `assign` needs the output to be the lhs type.
+    /// ```compile_fail
+    /// use std::ops::Mul;
+    /// use xops::binop;
+    ///
+    /// #[derive(Clone, Copy)]
+    /// struct A(i32);
+    ///
+    /// #[binop(assign)]
+    /// impl Mul for A {
+    ///     type Output = i32;
+    ///
+    ///     fn mul(self, rhs: A) -> i32 {
+    ///         self.0 * rhs.0
+    ///     }
+    /// }
+    /// ```
+    struct AssignWithForeignOutput;
+
+    /// `assign` on a reference lhs needs `derefs` to derive the owned form it assigns to.
+    /// ```compile_fail
+    /// use std::ops::Add;
+    /// use xops::binop;
+    ///
+    /// #[derive(Clone, Default)]
+    /// struct A(Vec<i32>);
+    ///
+    /// #[binop(assign(take))]
+    /// impl Add<&A> for &A {
+    ///     type Output = A;
+    ///
+    ///     fn add(self, rhs: &A) -> A {
+    ///         A(self.0.iter().chain(&rhs.0).copied().collect())
+    ///     }
+    /// }
+    /// ```
+    struct AssignToReferenceLhs;
+
+    /// `sum` on a reference lhs needs `derefs` to derive the owned form it folds.
+    /// ```compile_fail
+    /// use std::ops::Add;
+    /// use xops::binop;
+    ///
+    /// struct A(Vec<i32>);
+    ///
+    /// #[binop(sum(zero = "A(Vec::new())"))]
+    /// impl Add<&A> for &A {
+    ///     type Output = A;
+    ///
+    ///     fn add(self, rhs: &A) -> A {
+    ///         A(self.0.iter().chain(&rhs.0).copied().collect())
+    ///     }
+    /// }
+    /// ```
+    struct SumOfReferenceLhs;
+
+    /// `product` needs an implementation of `Mul`.
+    /// ```compile_fail
+    /// use std::ops::Add;
+    /// use xops::binop;
+    ///
+    /// #[derive(Clone, Copy)]
+    /// struct A(i32);
+    ///
+    /// #[binop(product(one = "A(1)"))]
+    /// impl Add for A {
+    ///     type Output = A;
+    ///
+    ///     fn add(self, rhs: A) -> A {
+    ///         A(self.0 + rhs.0)
+    ///     }
+    /// }
+    /// ```
+    struct ProductOfAdd;
+
+    /// `derive_sub` needs an implementation of `Add`.
+    /// ```compile_fail
+    /// use std::ops::Mul;
+    /// use xops::binop;
+    ///
+    /// #[derive(Clone, Copy)]
+    /// struct A(i32);
+    ///
+    /// #[binop(derive_sub)]
+    /// impl Mul for A {
+    ///     type Output = A;
+    ///
+    ///     fn mul(self, rhs: A) -> A {
+    ///         A(self.0 * rhs.0)
+    ///     }
+    /// }
+    /// ```
+    struct DeriveSubFromMul;
+
+    /// `refs_clone` needs a method taking both operands by value.
+    /// ```compile_fail
+    /// use xops::binop;
+    ///
+    /// trait Dot<Rhs> {
+    ///     type Output;
+    ///     fn dot(&self, rhs: &Rhs) -> Self::Output;
+    /// }
+    ///
+    /// #[derive(Clone)]
+    /// struct A(i32);
+    ///
+    /// #[binop(refs_clone)]
+    /// impl Dot<A> for A {
+    ///     type Output = i32;
+    ///
+    ///     fn dot(&self, rhs: &A) -> i32 {
+    ///         self.0 * rhs.0
+    ///     }
+    /// }
+    /// ```
+    struct RefsCloneByReference;
+
+    /// `Add` is implemented by `add`, so a method `sub` is rejected.
+    /// ```compile_fail
+    /// use std::ops::Add;
+    /// use xops::binop;
+    ///
+    /// struct A(i32);
+    ///
+    /// #[binop]
+    /// impl Add for A {
+    ///     type Output = A;
+    ///
+    ///     fn sub(self, rhs: A) -> A {
+    ///         A(self.0 - rhs.0)
+    ///     }
+    /// }
+    /// ```
+    struct CatalogMethodMismatch;
+
+    /// `Neg` is a unary operation, which `binop` leaves to `unop`.
+    /// ```compile_fail
+    /// use std::ops::Neg;
+    /// use xops::binop;
+    ///
+    /// struct A(i32);
+    ///
+    /// #[binop]
+    /// impl Neg for A {
+    ///     type Output = A;
+    ///
+    ///     fn neg(self) -> A {
+    ///         A(-self.0)
+    ///     }
+    /// }
+    /// ```
+    struct CatalogUnaryOperation;
+
+    /// `PartialOrd` is a comparison, which `binop` does not support.
+    /// ```compile_fail
+    /// use xops::binop;
+    ///
+    /// #[derive(PartialEq)]
+    /// struct A(i32);
+    ///
+    /// #[binop]
+    /// impl PartialOrd for A {
+    ///     fn partial_cmp(self, rhs: A) -> Option<std::cmp::Ordering> {
+    ///         self.0.partial_cmp(&rhs.0)
+    ///     }
+    /// }
+    /// ```
+    struct CatalogComparison;
+
+    /// `Add` names its output type `Output`.
+    /// ```compile_fail
+    /// use std::ops::Add;
+    /// use xops::binop;
+    ///
+    /// struct A(i32);
+    ///
+    /// #[binop]
+    /// impl Add for A {
+    ///     type Sum = A;
+    ///
+    ///     fn add(self, rhs: A) -> A {
+    ///         A(self.0 + rhs.0)
+    ///     }
+    /// }
+    /// ```
+    struct CatalogOutputName;
+
+    /// `Add` takes no argument besides the rhs type.
+    /// ```compile_fail
+    /// use std::ops::Add;
+    /// use xops::binop;
+    ///
+    /// struct A(i32);
+    ///
+    /// #[binop]
+    /// impl Add<A, A> for A {
+    ///     type Output = A;
+    ///
+    ///     fn add(self, rhs: A) -> A {
+    ///         A(self.0 + rhs.0)
+    ///     }
+    /// }
+    /// ```
+    struct CatalogExtraTraitArgument;
+
+    /// `Sub` is not commutative, so plain `commute` is rejected in favour of `commute(unchecked)`.
+    /// ```compile_fail
+    /// use std::ops::Sub;
+    /// use xops::binop;
+    ///
+    /// struct A(i32);
+    /// struct B(i32);
+    ///
+    /// #[binop(commute)]
+    /// impl Sub<B> for A {
+    ///     type Output = i32;
+    ///
+    ///     fn sub(self, rhs: B) -> i32 {
+    ///         self.0 - rhs.0
+    ///     }
+    /// }
+    /// ```
+    struct CommuteNonCommutative;
 }
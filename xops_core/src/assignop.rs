@@ -0,0 +1,44 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+mod parse;
+pub use parse::*;
+
+mod write;
+
+use crate::{utils::print_tokens, Error};
+
+impl AssignOpImpl {
+    pub fn expand(&self, args: AssignOpArgs) -> TokenStream {
+        expand(&args, self.resolve_self())
+    }
+}
+
+fn expand(args: &AssignOpArgs, impl_: AssignOpImpl) -> TokenStream {
+    if args.dev_print {
+        dbg!(args);
+        print_tokens("assignop impltn", &impl_);
+    }
+
+    if impl_.op_trait().is_none() {
+        let err = Error::new_spanned(
+            &impl_.trait_,
+            "expected a compound assignment trait such as `AddAssign`",
+        )
+        .to_compile_error();
+        return quote!(#impl_ #err);
+    }
+
+    let expanded_own = impl_.op_own();
+    let expanded_ref = impl_.op_ref_clone();
+    let expanded_own_deref = impl_.try_op_own_deref();
+    let expanded_ref_deref = impl_.try_op_ref_clone_deref();
+
+    quote! {
+        #impl_
+        #expanded_own
+        #expanded_ref
+        #expanded_own_deref
+        #expanded_ref_deref
+    }
+}
@@ -0,0 +1,241 @@
+use std::convert::TryFrom;
+
+use darling::FromMeta;
+use quote::quote;
+use syn::{
+    parenthesized,
+    parse::{Parse, ParseStream},
+    token, Attribute, Block, FnArg, GenericArgument, Generics, Ident, ImplItem, ImplItemMethod,
+    ItemImpl, Path, PathArguments, Receiver, ReturnType, Token, Type, WhereClause,
+};
+
+use crate::utils::{parse_fn_ident, position_by_role};
+
+// structs -----------------------------------------------------------------------------------------
+
+/// Arguments for the macro `#[assignop(...)]`
+#[derive(Clone, Default, FromMeta, Debug)]
+#[darling(default)]
+pub struct AssignOpArgs {
+    pub dev_print: bool,
+}
+
+/// Method implementation for a compound assignment operation:
+/// `fn op_assign(&mut self, rhs: B) { .. }`
+#[derive(Clone, Debug)]
+pub struct AssignOpFn {
+    pub attrs: Vec<Attribute>,
+    pub fn_token: Token![fn],
+    pub ident: Ident,
+    pub paren_token: token::Paren,
+    pub lhs_arg: Receiver,
+    pub comma_token: Token![,],
+    pub rhs_arg: FnArg,
+    pub where_clause: Option<WhereClause>,
+    pub block: Block,
+}
+
+/// An impl block for a compound assignment operation.
+///
+/// This is the counterpart of [`BinOpImpl`](crate::BinOpImpl) for traits like `AddAssign`, and
+/// is likewise built from a `syn::ItemImpl`, in which the operator method is found by its role
+/// rather than its position.
+///
+/// Example:
+/// ```ignore
+/// impl AddAssign<B> for A {
+///     fn add_assign(&mut self, rhs: B) {
+///         ...
+///     }
+/// }
+/// ```
+///
+/// The operator method is the method taking `&mut self` and one argument whose name is the trait
+/// name in snake case, or else the only such method. Any other items are kept in `items`; as the
+/// derived implementations are of another trait, they are not copied there.
+#[derive(Clone, Debug)]
+pub struct AssignOpImpl {
+    pub attrs: Vec<Attribute>,
+    pub defaultness: Option<Token![default]>,
+    pub unsafety: Option<Token![unsafe]>,
+    pub impl_token: Token![impl],
+    pub generics: Generics,
+    pub trait_: Path,
+    pub lt_token: Option<Token![<]>,
+    pub rhs_ty: Type,
+    pub for_token: Token![for],
+    pub lhs_ty: Type,
+    pub brace_token: token::Brace,
+    pub item_fn: AssignOpFn,
+    pub items: Vec<ImplItem>,
+}
+
+// impl Parse --------------------------------------------------------------------------------------
+
+impl Parse for AssignOpFn {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        Ok(AssignOpFn {
+            attrs: input.call(Attribute::parse_outer)?,
+            fn_token: input.parse()?,
+            ident: input.call(parse_fn_ident)?,
+            paren_token: parenthesized!(content in input),
+            lhs_arg: content.parse()?,
+            comma_token: content.parse()?,
+            rhs_arg: content.parse()?,
+            where_clause: input.parse()?,
+            block: input.parse()?,
+        })
+    }
+}
+
+impl Parse for AssignOpImpl {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        AssignOpImpl::try_from(input.parse::<ItemImpl>()?)
+    }
+}
+
+// impl TryFrom ------------------------------------------------------------------------------------
+
+impl TryFrom<ImplItemMethod> for AssignOpFn {
+    type Error = syn::Error;
+
+    fn try_from(item: ImplItemMethod) -> syn::Result<Self> {
+        let sig = item.sig;
+        if !sig.generics.params.is_empty() {
+            return Err(syn::Error::new_spanned(
+                &sig.generics.params,
+                "operator methods take no generic parameters; declare them on the impl instead",
+            ));
+        }
+        if let ReturnType::Type(arrow_token, out_ty) = &sig.output {
+            return Err(syn::Error::new_spanned(
+                quote!(#arrow_token #out_ty),
+                "compound assignment methods return nothing",
+            ));
+        }
+
+        let mut inputs = sig.inputs.into_pairs();
+        let (lhs_arg, comma_token, rhs_arg) = match (inputs.next(), inputs.next(), inputs.next()) {
+            (Some(lhs_pair), Some(rhs_pair), None) => match lhs_pair.into_tuple() {
+                (FnArg::Receiver(lhs_arg), Some(comma_token)) => {
+                    (lhs_arg, comma_token, rhs_pair.into_value())
+                }
+                (lhs_arg, _) => {
+                    return Err(syn::Error::new_spanned(
+                        lhs_arg,
+                        "expected the receiver `&mut self`",
+                    ))
+                }
+            },
+            _ => {
+                return Err(syn::Error::new(
+                    sig.paren_token.span,
+                    "expected `&mut self` and one argument, as in `(&mut self, rhs: B)`",
+                ))
+            }
+        };
+
+        Ok(AssignOpFn {
+            attrs: item.attrs,
+            fn_token: sig.fn_token,
+            ident: sig.ident,
+            paren_token: sig.paren_token,
+            lhs_arg,
+            comma_token,
+            rhs_arg,
+            where_clause: sig.generics.where_clause,
+            block: item.block,
+        })
+    }
+}
+
+impl TryFrom<ItemImpl> for AssignOpImpl {
+    type Error = syn::Error;
+
+    fn try_from(item: ItemImpl) -> syn::Result<Self> {
+        let (mut trait_, for_token) = match item.trait_ {
+            Some((None, trait_, for_token)) => (trait_, for_token),
+            Some((Some(bang), ..)) => {
+                return Err(syn::Error::new_spanned(
+                    bang,
+                    "negative impls are not supported",
+                ))
+            }
+            None => {
+                return Err(syn::Error::new_spanned(
+                    &item.self_ty,
+                    "expected a trait implementation, as in `impl AddAssign<B> for A`",
+                ))
+            }
+        };
+        let lhs_ty = *item.self_ty;
+
+        let path_args = match trait_.segments.last_mut() {
+            Some(segment) => std::mem::replace(&mut segment.arguments, PathArguments::None),
+            None => PathArguments::None,
+        };
+        let (lt_token, rhs_ty) = match path_args {
+            PathArguments::None => (None, lhs_ty.clone()),
+            PathArguments::AngleBracketed(args) if args.args.is_empty() => (None, lhs_ty.clone()),
+            PathArguments::AngleBracketed(args) => match args.args.iter().collect::<Vec<_>>()[..] {
+                [GenericArgument::Type(rhs_ty)] => (Some(args.lt_token), rhs_ty.clone()),
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        &args,
+                        "expected a single rhs type, as in `AddAssign<B>`",
+                    ))
+                }
+            },
+            PathArguments::Parenthesized(args) => {
+                return Err(syn::Error::new_spanned(
+                    args,
+                    "expected a single rhs type, as in `AddAssign<B>`",
+                ))
+            }
+        };
+
+        let op_fn = trait_
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string().to_lowercase())
+            .unwrap_or_default();
+        let brace_span = item.brace_token.span;
+        let mut items = item.items;
+
+        let fn_pos = position_by_role(&items, |item| match item {
+            ImplItem::Method(method)
+                if method.sig.inputs.len() == 2 && method.sig.receiver().is_some() =>
+            {
+                Some(method.sig.ident.to_string().replace('_', "") == op_fn)
+            }
+            _ => None,
+        })
+        .ok_or_else(|| {
+            syn::Error::new(
+                brace_span,
+                "expected the operator method, as in `fn add_assign(&mut self, rhs: B)`",
+            )
+        })?;
+        let item_fn = match items.remove(fn_pos) {
+            ImplItem::Method(item_fn) => AssignOpFn::try_from(item_fn)?,
+            _ => unreachable!(),
+        };
+
+        Ok(AssignOpImpl {
+            attrs: item.attrs,
+            defaultness: item.defaultness,
+            unsafety: item.unsafety,
+            impl_token: item.impl_token,
+            generics: item.generics,
+            trait_,
+            lt_token,
+            rhs_ty,
+            for_token,
+            lhs_ty,
+            brace_token: item.brace_token,
+            item_fn,
+            items,
+        })
+    }
+}
@@ -0,0 +1,265 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens, TokenStreamExt};
+use syn::{parse_quote, Ident, Path, Type};
+
+use crate::{
+    catalog,
+    utils::{
+        add_bound, allow_named_lifetimes, name_lifetime, named_lifetimes, prune_generics,
+        resolve_self, TypeConversion,
+    },
+    AssignOpFn, AssignOpImpl, BinOpImpl,
+};
+
+impl AssignOpImpl {
+    /// Returns `self` with each `Self` in `rhs_ty` and the where clause replaced by `lhs_ty`.
+    ///
+    /// Derived implementations copy these tokens into impls whose `Self` is a different type, so
+    /// every derivation should start from a resolved implementation.
+    pub fn resolve_self(&self) -> Self {
+        let lhs_ty = &self.lhs_ty;
+        let mut resolved = self.clone();
+
+        resolved.rhs_ty = resolve_self(&self.rhs_ty, lhs_ty);
+        resolved.generics.where_clause = self
+            .generics
+            .where_clause
+            .as_ref()
+            .map(|where_clause| resolve_self(where_clause, lhs_ty));
+
+        resolved
+    }
+
+    /// Returns the path and method identifier of the binary operation corresponding to `self`,
+    /// i.e., `Op` and `op` for `impl OpAssign<B> for A { fn op_assign(..) }`.
+    ///
+    /// The ten `*Assign` traits of `std::ops` are mapped into `::core::ops`, whereas any other
+    /// trait `path::to::OpAssign` is mapped to `path::to::Op`.
+    ///
+    /// If the trait or method is not named with the `Assign`/`_assign` suffix, this returns
+    /// `None`.
+    pub fn op_trait(&self) -> Option<(Path, Ident)> {
+        let assign_ident = self.trait_.segments.last()?.ident.to_string();
+        let assign_fn = self.item_fn.ident.to_string();
+        let op_ident = format_ident!("{}", assign_ident.strip_suffix("Assign")?);
+        let op_fn = format_ident!("{}", assign_fn.strip_suffix("_assign")?);
+
+        let op_trait = match catalog::lookup_assign(&self.trait_) {
+            Some(info) => info.path(),
+            None => {
+                let mut op_trait = self.trait_.clone();
+                op_trait.segments.last_mut()?.ident = op_ident;
+                op_trait
+            }
+        };
+
+        Some((op_trait, op_fn))
+    }
+
+    /// Returns an implementation of `A op B` utilizing `A op= B`, which reuses `self`.
+    ///
+    /// In other words, if `self` is of the form
+    /// ```ignore
+    /// impl OpAssign<B> for A {
+    ///     ...
+    /// }
+    /// ```
+    /// then this produces the implementation
+    /// ```ignore
+    /// impl Op<B> for A {
+    ///     type Output = A;
+    ///
+    ///     fn op(self, rhs: B) -> Self::Output {
+    ///         let mut lhs = self;
+    ///         OpAssign::op_assign(&mut lhs, rhs);
+    ///         lhs
+    ///     }
+    /// }
+    /// ```
+    pub fn op_own(&self) -> Option<BinOpImpl> {
+        let lhs_ty = self.lhs_ty.clone();
+        let rhs_ty = self.rhs_ty.clone();
+
+        self.delegate(lhs_ty, rhs_ty, quote!(self), quote!(rhs))
+    }
+
+    /// Returns an implementation of `&A op B` utilizing `A op= B`, which clones `self` once.
+    ///
+    /// The macro user must ensure that `A: Clone`; if `A` involves generic parameters, the bound
+    /// `A: Clone` is added to the where clause.
+    ///
+    /// In other words, if `self` is of the form
+    /// ```ignore
+    /// impl OpAssign<B> for A {
+    ///     ...
+    /// }
+    /// ```
+    /// then this produces the implementation
+    /// ```ignore
+    /// impl Op<B> for &A {
+    ///     type Output = A;
+    ///
+    ///     fn op(self, rhs: B) -> Self::Output {
+    ///         let mut lhs = self.clone();
+    ///         OpAssign::op_assign(&mut lhs, rhs);
+    ///         lhs
+    ///     }
+    /// }
+    /// ```
+    pub fn op_ref_clone(&self) -> Option<BinOpImpl> {
+        let lhs_ty = self.lhs_ty.as_ref();
+        let rhs_ty = self.rhs_ty.clone();
+
+        self.delegate(lhs_ty, rhs_ty, quote!(self.clone()), quote!(rhs))
+    }
+
+    /// If `rhs_ty = &B`, this returns an implementation of `A op B` utilizing `A op= &B`.
+    ///
+    /// If `rhs_ty` is not a shared reference type, this returns `None`.
+    ///
+    /// In other words, if `self` is of the form
+    /// ```ignore
+    /// impl OpAssign<&B> for A {
+    ///     ...
+    /// }
+    /// ```
+    /// then this produces the implementation
+    /// ```ignore
+    /// impl Op<B> for A {
+    ///     type Output = A;
+    ///
+    ///     fn op(self, rhs: B) -> Self::Output {
+    ///         let mut lhs = self;
+    ///         OpAssign::op_assign(&mut lhs, &rhs);
+    ///         lhs
+    ///     }
+    /// }
+    /// ```
+    pub fn try_op_own_deref(&self) -> Option<BinOpImpl> {
+        let lhs_ty = self.lhs_ty.clone();
+        let rhs_ty = self
+            .rhs_ty
+            .as_deref()
+            .filter(|_| !self.rhs_ty.is_mut_ref())?;
+
+        self.delegate(lhs_ty, rhs_ty, quote!(self), quote!(&rhs))
+    }
+
+    /// If `rhs_ty = &B`, this returns an implementation of `&A op B` utilizing `A op= &B`.
+    ///
+    /// If `rhs_ty` is not a shared reference type, this returns `None`. As with
+    /// [`AssignOpImpl::op_ref_clone`], the macro user must ensure that `A: Clone`.
+    ///
+    /// In other words, if `self` is of the form
+    /// ```ignore
+    /// impl OpAssign<&B> for A {
+    ///     ...
+    /// }
+    /// ```
+    /// then this produces the implementation
+    /// ```ignore
+    /// impl Op<B> for &A {
+    ///     type Output = A;
+    ///
+    ///     fn op(self, rhs: B) -> Self::Output {
+    ///         let mut lhs = self.clone();
+    ///         OpAssign::op_assign(&mut lhs, &rhs);
+    ///         lhs
+    ///     }
+    /// }
+    /// ```
+    pub fn try_op_ref_clone_deref(&self) -> Option<BinOpImpl> {
+        let lhs_ty = self.lhs_ty.as_ref();
+        let rhs_ty = self
+            .rhs_ty
+            .as_deref()
+            .filter(|_| !self.rhs_ty.is_mut_ref())?;
+
+        self.delegate(lhs_ty, rhs_ty, quote!(self.clone()), quote!(&rhs))
+    }
+
+    /// Returns an implementation of `lhs_ty op rhs_ty` whose method assigns `rhs_expr` onto
+    /// `lhs_expr`, where `self` and `rhs` are the operands of the new method.
+    ///
+    /// If the trait of `self` has no corresponding binary operation, this returns `None`.
+    /// Operand references with elided lifetimes get fresh named lifetimes `'lhs` and `'rhs`, and
+    /// generic parameters which the new implementation no longer mentions are pruned.
+    fn delegate(
+        &self,
+        lhs_ty: Type,
+        rhs_ty: Type,
+        lhs_expr: TokenStream,
+        rhs_expr: TokenStream,
+    ) -> Option<BinOpImpl> {
+        let (op_trait, op_fn) = self.op_trait()?;
+        let assign_trait = &self.trait_;
+        let assign_fn = &self.item_fn.ident;
+        let out_ty = &self.lhs_ty;
+
+        let mut generics = self.generics.clone();
+        let lhs_ty = name_lifetime(lhs_ty, &mut generics, "lhs");
+        let rhs_ty = name_lifetime(rhs_ty, &mut generics, "rhs");
+        let named = named_lifetimes(&self.generics, &generics);
+        prune_generics(&mut generics, &quote!(#lhs_ty #rhs_ty #out_ty));
+        if lhs_ty.as_deref().is_some() {
+            add_bound(&mut generics, out_ty, quote!(::core::clone::Clone));
+        }
+        let where_clause = &generics.where_clause;
+
+        let mut derived: BinOpImpl = parse_quote! {
+            impl #generics #op_trait<#rhs_ty> for #lhs_ty #where_clause {
+                type Output = #out_ty;
+
+                fn #op_fn(self, rhs: #rhs_ty) -> Self::Output {
+                    let mut lhs = #lhs_expr;
+                    #assign_trait::#assign_fn(&mut lhs, #rhs_expr);
+                    lhs
+                }
+            }
+        };
+        let tokens = derived.to_token_stream();
+        derived.attrs = self.attrs.clone();
+        allow_named_lifetimes(&mut derived.attrs, &named, &tokens);
+        Some(derived)
+    }
+}
+
+// impl ToTokens -----------------------------------------------------------------------------------
+
+impl ToTokens for AssignOpFn {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.append_all(&self.attrs);
+        self.fn_token.to_tokens(tokens);
+        self.ident.to_tokens(tokens);
+        self.paren_token.surround(tokens, |tokens| {
+            self.lhs_arg.to_tokens(tokens);
+            self.comma_token.to_tokens(tokens);
+            self.rhs_arg.to_tokens(tokens);
+        });
+        self.where_clause.to_tokens(tokens);
+        self.block.to_tokens(tokens);
+    }
+}
+
+impl ToTokens for AssignOpImpl {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let rhs_ty = &self.rhs_ty;
+
+        tokens.append_all(&self.attrs);
+        self.defaultness.to_tokens(tokens);
+        self.unsafety.to_tokens(tokens);
+        self.impl_token.to_tokens(tokens);
+        self.generics.to_tokens(tokens);
+        self.trait_.to_tokens(tokens);
+        if self.lt_token.is_some() {
+            tokens.append_all(quote!(<#rhs_ty>));
+        }
+        self.for_token.to_tokens(tokens);
+        self.lhs_ty.to_tokens(tokens);
+        self.generics.where_clause.to_tokens(tokens);
+        self.brace_token.surround(tokens, |tokens| {
+            self.item_fn.to_tokens(tokens);
+            tokens.append_all(&self.items);
+        });
+    }
+}
@@ -1,52 +1,435 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
+use darling::util::SpannedValue;
 use quote::quote;
+use syn::{spanned::Spanned, Type};
 
 mod parse;
 pub use parse::*;
 
 mod write;
-pub use write::*;
 
-use crate::utils::print_tokens;
+use crate::{
+    catalog,
+    utils::{placeholder, pretty, print_tokens, tokens_eq, TypeConversion},
+    Error, Result,
+};
 
 impl BinOpImpl {
     pub fn expand(&self, args: BinOpArgs) -> TokenStream {
-        expand(args, self.clone())
+        let base = self.resolve_self();
+        if let Err(err) = check(&args, &base) {
+            let err = err.to_compile_error();
+            return quote!(#base #err);
+        }
+
+        if args.lhs_types.is_some() || args.rhs_types.is_some() {
+            return expand_types(args, self);
+        }
+
+        let args = BinOpArgs {
+            base: Some(Box::new(base.clone())),
+            ..args
+        };
+
+        expand(&args, base)
+    }
+}
+
+/// Checks that every argument applies to the given implementation `impl_`.
+fn check(args: &BinOpArgs, impl_: &BinOpImpl) -> Result<()> {
+    let lhs_ty = &impl_.lhs_ty;
+    let rhs_ty = &impl_.rhs_ty;
+    let out_ty = impl_.output_ty();
+    let derefs = *args.derefs || *args.all_refs;
+    let mut errors = Vec::new();
+
+    let non_commutative = catalog::lookup(&impl_.trait_).filter(|info| !info.commutative);
+    if let (Some(commute), Some(info)) = (&args.commute, non_commutative) {
+        if **commute == CommuteMode::Checked {
+            errors.push(Error::new(
+                commute.span(),
+                format!(
+                    "`{}` is not commutative, so `commute` would define `b {} a` as `a {} b`; use \
+                     `anticommute` or `commute_with = \"..\"` instead, or `commute(unchecked)` if \
+                     this is intended",
+                    info.trait_name, info.symbol, info.symbol,
+                ),
+            ));
+        }
+    }
+
+    if !impl_.operands_by_value() {
+        let flag = |flag: &SpannedValue<bool>| (**flag).then(|| flag.span());
+        let by_value_args = [
+            ("refs_clone", flag(&args.refs_clone)),
+            ("refs_copy", flag(&args.refs_copy)),
+            ("derefs", flag(&args.derefs)),
+            ("all_refs", flag(&args.all_refs)),
+            ("mut_refs", flag(&args.mut_refs)),
+            ("boxed", flag(&args.boxed)),
+            ("rc", flag(&args.rc)),
+            ("arc", flag(&args.arc)),
+            ("cow", flag(&args.cow)),
+            ("lhs_from", args.lhs_from.as_ref().map(|arg| arg.span())),
+            ("rhs_from", args.rhs_from.as_ref().map(|arg| arg.span())),
+            ("assign", args.assign.as_ref().map(|arg| arg.span())),
+            ("sum", args.sum.as_ref().map(|arg| arg.span())),
+            ("product", args.product.as_ref().map(|arg| arg.span())),
+            ("derive_sub", flag(&args.derive_sub)),
+            ("derive_div", args.derive_div.as_ref().map(|arg| arg.span())),
+        ];
+        let item_fn = &impl_.item_fn;
+        for (arg, span) in by_value_args.iter() {
+            if let Some(span) = span {
+                errors.push(Error::new(
+                    *span,
+                    format!(
+                        "`{}` needs a method taking both operands by value; `{}` takes `{}` and \
+                         `{}`",
+                        arg,
+                        item_fn.ident,
+                        pretty(&item_fn.lhs_arg),
+                        pretty(&item_fn.rhs_arg),
+                    ),
+                ));
+            }
+        }
+        return Error::combine_all(errors);
+    }
+
+    if *args.refs_clone && *args.refs_copy && !(*args.derefs || *args.all_refs) {
+        errors.push(Error::new(
+            args.refs_copy.span(),
+            "`refs_clone` and `refs_copy` each derive the reference forms, so together they would \
+             derive them twice; keep one of them",
+        ));
+    }
+
+    if *args.derefs
+        && !(*args.all_refs || *args.refs_clone || *args.refs_copy)
+        && lhs_ty.as_deref().is_none()
+        && rhs_ty.as_deref().is_none()
+    {
+        errors.push(Error::new(
+            args.derefs.span(),
+            format!(
+                "`derefs` needs at least one reference operand; lhs `{}` and rhs `{}` are owned",
+                pretty(lhs_ty),
+                pretty(rhs_ty),
+            ),
+        ));
+    }
+
+    let shared_ref = |ty: &Type| ty.as_deref().is_some() && !ty.is_mut_ref();
+    let derives_ref = *args.all_refs || *args.refs_clone || *args.refs_copy;
+    if *args.mut_refs && !derives_ref && !shared_ref(lhs_ty) && !shared_ref(rhs_ty) {
+        errors.push(Error::new(
+            args.mut_refs.span(),
+            format!(
+                "`mut_refs` needs a shared reference operand to reborrow into, or `refs_clone` or \
+                 `refs_copy` to derive one; lhs `{}` and rhs `{}` are not shared references",
+                pretty(lhs_ty),
+                pretty(rhs_ty),
+            ),
+        ));
+    }
+
+    if let Some(assign) = &args.assign {
+        let reason = match lhs_ty.as_deref() {
+            Some(owned_ty) if tokens_eq(out_ty, &owned_ty) && !derefs => Some(format!(
+                "the lhs `{}` is a reference; add `derefs` to assign to its owned form",
+                pretty(lhs_ty),
+            )),
+            Some(owned_ty) if !tokens_eq(out_ty, &owned_ty) => Some(format!(
+                "the output `{}` differs from the owned lhs `{}`",
+                pretty(out_ty),
+                pretty(&owned_ty),
+            )),
+            None if !impl_.outputs_lhs() => Some(format!(
+                "the output `{}` differs from the lhs `{}`",
+                pretty(out_ty),
+                pretty(lhs_ty),
+            )),
+            _ => None,
+        };
+        if let Some(reason) = reason {
+            errors.push(Error::new(
+                assign.span(),
+                format!("`assign` does not apply: {}", reason),
+            ));
+        }
+    }
+
+    if let Some(sum) = &args.sum {
+        errors.extend(check_fold("sum", "Add", sum.span(), derefs, impl_));
+    }
+    if let Some(product) = &args.product {
+        errors.extend(check_fold("product", "Mul", product.span(), derefs, impl_));
+    }
+    if *args.derive_sub {
+        errors.extend(check_op("derive_sub", "Add", args.derive_sub.span(), impl_));
+    }
+    if let Some(derive_div) = &args.derive_div {
+        errors.extend(check_op("derive_div", "Mul", derive_div.span(), impl_));
+    }
+
+    Error::combine_all(errors)
+}
+
+/// Checks that `impl_` implements `op`, as needed by the argument `arg`.
+fn check_op(arg: &str, op: &str, span: Span, impl_: &BinOpImpl) -> Option<Error> {
+    if impl_.op_ident().is_some_and(|op_ident| op_ident == op) {
+        return None;
     }
+
+    Some(Error::new(
+        span,
+        format!(
+            "`{}` needs an implementation of `{}`; found `{}`",
+            arg,
+            op,
+            pretty(&impl_.trait_),
+        ),
+    ))
 }
 
-fn expand(args: BinOpArgs, impl_: BinOpImpl) -> TokenStream {
+/// Checks that `impl_` implements `op` with the lhs type as its rhs and output (up to
+/// references), as needed to fold with the argument `arg`.
+///
+/// A reference lhs is folded in its owned form, which only `derefs` derives.
+fn check_fold(arg: &str, op: &str, span: Span, derefs: bool, impl_: &BinOpImpl) -> Option<Error> {
+    let owned = |ty: &Type| match ty.as_deref() {
+        Some(owned_ty) if !ty.is_mut_ref() => owned_ty,
+        _ => ty.clone(),
+    };
+    let lhs_ty = &impl_.lhs_ty;
+    let rhs_ty = &impl_.rhs_ty;
+    let out_ty = impl_.output_ty();
+    let owned_lhs = owned(lhs_ty);
+    let folds = impl_.op_ident().is_some_and(|op_ident| op_ident == op)
+        && tokens_eq(&owned_lhs, &owned(rhs_ty));
+    if folds && impl_.outputs_lhs() {
+        return None;
+    }
+    if folds && !tokens_eq(&owned_lhs, lhs_ty) && tokens_eq(out_ty, &owned_lhs) {
+        if derefs {
+            return None;
+        }
+        return Some(Error::new(
+            span,
+            format!(
+                "`{}` folds the owned lhs; add `derefs` to derive `{}` for `{}` from `{}`",
+                arg,
+                pretty(&impl_.trait_),
+                pretty(&owned_lhs),
+                pretty(lhs_ty),
+            ),
+        ));
+    }
+
+    Some(Error::new(
+        span,
+        format!(
+            "`{}` needs an implementation of `{}` whose rhs and output are the lhs type; found \
+             `{}<{}>` for `{}` with output `{}`",
+            arg,
+            op,
+            pretty(&impl_.trait_),
+            pretty(rhs_ty),
+            pretty(lhs_ty),
+            pretty(out_ty),
+        ),
+    ))
+}
+
+fn expand_types(args: BinOpArgs, impl_: &BinOpImpl) -> TokenStream {
+    let new_args = BinOpArgs {
+        lhs_types: None,
+        rhs_types: None,
+        ..args.clone()
+    };
+
+    let instances = instantiate_all(&impl_.lhs_ty, &args.lhs_types, vec![impl_.clone()])
+        .and_then(|impls| instantiate_all(&impl_.rhs_ty, &args.rhs_types, impls));
+
+    match instances {
+        Ok(impls) => impls
+            .iter()
+            .map(|instance| instance.expand(new_args.clone()))
+            .collect(),
+        Err(err) => err.to_compile_error(),
+    }
+}
+
+fn instantiate_all(
+    operand: &Type,
+    types: &Option<TypeList>,
+    impls: Vec<BinOpImpl>,
+) -> Result<Vec<BinOpImpl>> {
+    let types = match types {
+        Some(TypeList(types)) => types,
+        None => return Ok(impls),
+    };
+    let placeholder = placeholder(operand).ok_or_else(|| {
+        Error::new_spanned(operand, "expected a type name to substitute, such as `T`")
+    })?;
+
+    impls
+        .iter()
+        .flat_map(|impl_| types.iter().map(move |ty| impl_.instantiate(placeholder, ty)))
+        .collect()
+}
+
+fn expand(args: &BinOpArgs, impl_: BinOpImpl) -> TokenStream {
     if args.dev_print {
         dbg!(args);
         print_tokens("binop impltn", &impl_);
     }
 
-    if args.commute {
+    if *args.derive_sub || args.derive_div.is_some() {
+        expand_derived_ops(args, impl_)
+    } else if args.lhs_from.is_some() || args.rhs_from.is_some() {
+        expand_from(args, impl_)
+    } else if args.commute.is_some() || *args.anticommute || args.commute_with.is_some() {
         expand_commute(args, impl_)
-    } else if args.refs_clone {
+    } else if *args.boxed || *args.rc || *args.arc || *args.cow {
+        expand_pointers(args, impl_)
+    } else if *args.all_refs || ((*args.refs_clone || *args.refs_copy) && *args.derefs) {
+        expand_all_refs(args, impl_)
+    } else if *args.refs_clone {
         expand_refs_clone(args, impl_)
-    } else if args.derefs {
+    } else if *args.refs_copy {
+        expand_refs_copy(args, impl_)
+    } else if *args.derefs {
         expand_derefs(args, impl_)
+    } else if *args.mut_refs {
+        expand_mut_refs(args, impl_)
     } else {
-        quote!(#impl_)
+        let assign_impl = args
+            .assign
+            .as_ref()
+            .and_then(|strategy| impl_.assign(strategy));
+        let sum_impl = args.sum.as_ref().and_then(|sum| impl_.sum(&sum.zero));
+        let product_impl = args
+            .product
+            .as_ref()
+            .and_then(|product| impl_.product(&product.one));
+
+        let impl_ = match &args.base {
+            Some(base) if !args.literal_output => impl_.with_output_of(base),
+            _ => impl_,
+        };
+
+        quote! {
+            #impl_
+            #assign_impl
+            #sum_impl
+            #product_impl
+        }
     }
 }
 
-pub fn expand_option(args: BinOpArgs, option_impl: Option<BinOpImpl>) -> TokenStream {
+pub fn expand_option(args: &BinOpArgs, option_impl: Option<BinOpImpl>) -> TokenStream {
     match option_impl {
         Some(impl_) => expand(args, impl_),
         None => TokenStream::default(),
     }
 }
 
-fn expand_commute(args: BinOpArgs, impl_: BinOpImpl) -> TokenStream {
+fn expand_derived_ops(args: &BinOpArgs, impl_: BinOpImpl) -> TokenStream {
     let new_args = BinOpArgs {
-        commute: false,
-        ..args
+        derive_sub: SpannedValue::default(),
+        derive_div: None,
+        ..args.clone()
+    };
+    let derived_args = BinOpArgs {
+        commute: None,
+        anticommute: SpannedValue::default(),
+        commute_with: None,
+        ..new_args.clone()
     };
 
-    let expanded = impl_.expand(new_args);
-    let expanded_comm = impl_.commute().expand(new_args);
+    let expanded = expand(&new_args, impl_.clone());
+    let expanded_sub = if *args.derive_sub {
+        expand_option(&derived_args, impl_.sub_from_add())
+    } else {
+        TokenStream::default()
+    };
+    let expanded_div = match &args.derive_div {
+        Some(div) => expand_option(&derived_args, impl_.div_from_mul(&div.recip)),
+        None => TokenStream::default(),
+    };
+
+    quote! {
+        #expanded
+        #expanded_sub
+        #expanded_div
+    }
+}
+
+fn expand_from(args: &BinOpArgs, impl_: BinOpImpl) -> TokenStream {
+    let new_args = BinOpArgs {
+        lhs_from: None,
+        rhs_from: None,
+        ..args.clone()
+    };
+    let lhs_from = args.lhs_from.as_ref().map_or(&[][..], |types| &types.0);
+    let rhs_from = args.rhs_from.as_ref().map_or(&[][..], |types| &types.0);
+
+    let expanded = expand(&new_args, impl_.clone());
+    let expanded_lhs = lhs_from
+        .iter()
+        .map(|lhs_ty| expand(&new_args, impl_.lhs_from(lhs_ty)));
+    let expanded_rhs = rhs_from
+        .iter()
+        .map(|rhs_ty| expand(&new_args, impl_.rhs_from(rhs_ty)));
+
+    quote! {
+        #expanded
+        #(#expanded_lhs)*
+        #(#expanded_rhs)*
+    }
+}
+
+fn expand_commute(args: &BinOpArgs, impl_: BinOpImpl) -> TokenStream {
+    let new_args = BinOpArgs {
+        commute: None,
+        anticommute: SpannedValue::default(),
+        commute_with: None,
+        ..args.clone()
+    };
+
+    // `A op A` commutes into itself
+    if tokens_eq(&impl_.lhs_ty, &impl_.rhs_ty) {
+        return expand(&new_args, impl_);
+    }
+
+    let (commuted, arg, span) = if let Some(commute_fn) = &args.commute_with {
+        (impl_.commute_with(commute_fn), "commute_with", commute_fn.span())
+    } else if *args.anticommute {
+        (impl_.anticommute(), "anticommute", args.anticommute.span())
+    } else {
+        let span = args
+            .commute
+            .as_ref()
+            .map_or_else(Span::call_site, |arg| arg.span());
+        (impl_.commute(), "commute", span)
+    };
+
+    let expanded = expand(&new_args, impl_);
+    let expanded_comm = match commuted.uncovered_param() {
+        Some(param) => {
+            let message = format!(
+                "`{}` would implement `{}` for the type parameter `{}`, which the orphan rule \
+                 forbids; list concrete types with `rhs_types(..)` instead",
+                arg,
+                commuted.trait_.segments.last().unwrap().ident,
+                param,
+            );
+            Error::new(span, message).to_compile_error()
+        }
+        None => expand(&new_args, commuted),
+    };
 
     quote! {
         #expanded
@@ -54,16 +437,74 @@ fn expand_commute(args: BinOpArgs, impl_: BinOpImpl) -> TokenStream {
     }
 }
 
-fn expand_refs_clone(args: BinOpArgs, impl_: BinOpImpl) -> TokenStream {
+fn expand_pointers(args: &BinOpArgs, impl_: BinOpImpl) -> TokenStream {
+    let new_args = BinOpArgs {
+        boxed: SpannedValue::default(),
+        rc: SpannedValue::default(),
+        arc: SpannedValue::default(),
+        cow: SpannedValue::default(),
+        ..args.clone()
+    };
+    // references to an `Rc`, `Arc`, or `Cow` are never local, so the forms keep their ownership
+    let pointer_args = BinOpArgs {
+        refs_clone: SpannedValue::default(),
+        refs_copy: SpannedValue::default(),
+        derefs: SpannedValue::default(),
+        all_refs: SpannedValue::default(),
+        mut_refs: SpannedValue::default(),
+        ..new_args.clone()
+    };
+
+    let pointers = [
+        (*args.boxed, SmartPointer::Box),
+        (*args.rc, SmartPointer::Rc),
+        (*args.arc, SmartPointer::Arc),
+        (*args.cow, SmartPointer::Cow),
+    ];
+    let forms: Vec<BinOpImpl> = pointers
+        .iter()
+        .filter(|(requested, _)| *requested)
+        .flat_map(|&(_, pointer)| impl_.pointer_forms(pointer))
+        .collect();
+
+    let expanded = expand(&new_args, impl_);
+    let expanded_forms = forms.into_iter().map(|form| expand(&pointer_args, form));
+
+    quote! {
+        #expanded
+        #(#expanded_forms)*
+    }
+}
+
+fn expand_refs_clone(args: &BinOpArgs, impl_: BinOpImpl) -> TokenStream {
+    let new_args = BinOpArgs {
+        refs_clone: SpannedValue::default(),
+        ..args.clone()
+    };
+
+    let expanded = expand(&new_args, impl_.clone());
+    let expanded_ref_own = expand(&new_args, impl_.ref_lhs_clone());
+    let expanded_own_ref = expand(&new_args, impl_.ref_rhs_clone());
+    let expanded_ref_ref = expand(&new_args, impl_.ref_both_clone());
+
+    quote! {
+        #expanded
+        #expanded_ref_own
+        #expanded_own_ref
+        #expanded_ref_ref
+    }
+}
+
+fn expand_refs_copy(args: &BinOpArgs, impl_: BinOpImpl) -> TokenStream {
     let new_args = BinOpArgs {
-        refs_clone: false,
-        ..args
+        refs_copy: SpannedValue::default(),
+        ..args.clone()
     };
 
-    let expanded = expand(new_args, impl_.clone());
-    let expanded_ref_own = expand(new_args, impl_.ref_lhs_clone());
-    let expanded_own_ref = expand(new_args, impl_.ref_rhs_clone());
-    let expanded_ref_ref = expand(new_args, impl_.ref_both_clone());
+    let expanded = expand(&new_args, impl_.clone());
+    let expanded_ref_own = expand(&new_args, impl_.ref_lhs_copy());
+    let expanded_own_ref = expand(&new_args, impl_.ref_rhs_copy());
+    let expanded_ref_ref = expand(&new_args, impl_.ref_both_copy());
 
     quote! {
         #expanded
@@ -73,25 +514,97 @@ fn expand_refs_clone(args: BinOpArgs, impl_: BinOpImpl) -> TokenStream {
     }
 }
 
-fn expand_derefs(args: BinOpArgs, impl_: BinOpImpl) -> TokenStream {
+fn expand_derefs(args: &BinOpArgs, impl_: BinOpImpl) -> TokenStream {
+    let new_args = BinOpArgs {
+        derefs: SpannedValue::default(),
+        ..args.clone()
+    };
+
+    let forms = [
+        impl_.try_deref_lhs(),
+        impl_.try_deref_rhs(),
+        impl_.try_deref_both(),
+    ];
+    let dropped = forms
+        .iter()
+        .all(Option::is_none)
+        .then(|| derives_nothing("derefs", args.derefs.span(), &impl_));
+
+    let expanded = expand(&new_args, impl_);
+    let expanded_forms = forms
+        .iter()
+        .flatten()
+        .map(|form| expand(&new_args, form.clone()));
+
+    quote! {
+        #expanded
+        #(#expanded_forms)*
+        #dropped
+    }
+}
+
+fn expand_mut_refs(args: &BinOpArgs, impl_: BinOpImpl) -> TokenStream {
     let new_args = BinOpArgs {
-        derefs: false,
-        ..args
+        mut_refs: SpannedValue::default(),
+        ..args.clone()
     };
 
-    let expanded = expand(new_args, impl_.clone());
-    let expanded_deref_ref = expand_option(new_args, impl_.try_deref_lhs());
-    let expanded_ref_deref = expand_option(new_args, impl_.try_deref_rhs());
-    let expanded_deref_deref = expand_option(new_args, impl_.try_deref_both());
+    let forms = impl_.mut_ref_forms();
+
+    let expanded = expand(&new_args, impl_);
+    let expanded_forms = forms.into_iter().map(|form| expand(&new_args, form));
 
     quote! {
         #expanded
-        #expanded_deref_ref
-        #expanded_ref_deref
-        #expanded_deref_deref
+        #(#expanded_forms)*
     }
 }
 
+fn expand_all_refs(args: &BinOpArgs, impl_: BinOpImpl) -> TokenStream {
+    let new_args = BinOpArgs {
+        all_refs: SpannedValue::default(),
+        refs_clone: SpannedValue::default(),
+        refs_copy: SpannedValue::default(),
+        derefs: SpannedValue::default(),
+        ..args.clone()
+    };
+
+    let forms = if *args.refs_copy {
+        impl_.ref_forms_copy()
+    } else {
+        impl_.ref_forms()
+    };
+    let (arg, span) = if *args.all_refs {
+        ("all_refs", args.all_refs.span())
+    } else {
+        ("derefs", args.derefs.span())
+    };
+    let dropped = forms
+        .is_empty()
+        .then(|| derives_nothing(arg, span, &impl_));
+
+    let expanded = expand(&new_args, impl_);
+    let expanded_forms = forms.into_iter().map(|form| expand(&new_args, form));
+
+    quote! {
+        #expanded
+        #(#expanded_forms)*
+        #dropped
+    }
+}
+
+/// Returns the error for the argument `arg`, every form of which `impl_` drops since its output
+/// borrows from each reference operand the forms would take by value.
+fn derives_nothing(arg: &str, span: Span, impl_: &BinOpImpl) -> TokenStream {
+    let message = format!(
+        "`{}` derives nothing here: the output `{}` borrows from every reference operand it would \
+         take by value",
+        arg,
+        pretty(impl_.output_ty()),
+    );
+    Error::new(span, message).to_compile_error()
+}
+
 pub fn read_impl(impltn: BinOpImpl) -> TokenStream {
     let expanded = quote! {
         #impltn
@@ -1,25 +1,192 @@
-use darling::FromMeta;
+use std::convert::TryFrom;
+
+use darling::{util::SpannedValue, FromMeta};
+use quote::quote;
 use syn::{
-    braced, parenthesized,
+    parenthesized,
     parse::{Parse, ParseStream},
-    token, Attribute, Block, FnArg, Generics, Ident, Path, Receiver, Token, Type,
+    parse_quote, token, Attribute, Block, Expr, FnArg, GenericArgument, Generics, Ident, ImplItem,
+    ImplItemMethod, ImplItemType, ItemImpl, Lit, Meta, NestedMeta, Path, PathArguments, Receiver,
+    ReturnType, Token, Type, WhereClause,
+};
+
+use crate::{
+    catalog::{self, OpKind},
+    utils::{expr_from_lit, expr_from_meta, parse_fn_ident, position_by_role, tokens_eq},
 };
 
 // structs -----------------------------------------------------------------------------------------
 
 /// Arguments for the macro `#[binop(...)]`
-#[derive(Clone, Copy, Default, FromMeta, Debug)]
+#[derive(Clone, Default, FromMeta, Debug)]
 #[darling(default)]
 pub struct BinOpArgs {
     pub dev_print: bool,
-    pub commute: bool,
-    pub refs_clone: bool,
-    pub derefs: bool,
+    pub commute: Option<SpannedValue<CommuteMode>>,
+    pub anticommute: SpannedValue<bool>,
+    pub commute_with: Option<Path>,
+    pub refs_clone: SpannedValue<bool>,
+    pub refs_copy: SpannedValue<bool>,
+    pub derefs: SpannedValue<bool>,
+    pub all_refs: SpannedValue<bool>,
+    pub mut_refs: SpannedValue<bool>,
+    pub boxed: SpannedValue<bool>,
+    pub rc: SpannedValue<bool>,
+    pub arc: SpannedValue<bool>,
+    pub cow: SpannedValue<bool>,
+    pub assign: Option<SpannedValue<AssignStrategy>>,
+    pub sum: Option<SpannedValue<SumArgs>>,
+    pub product: Option<SpannedValue<ProductArgs>>,
+    pub derive_sub: SpannedValue<bool>,
+    pub derive_div: Option<SpannedValue<DeriveDivArgs>>,
+    pub literal_output: bool,
+    pub lhs_types: Option<TypeList>,
+    pub rhs_types: Option<TypeList>,
+    pub lhs_from: Option<SpannedValue<TypeList>>,
+    pub rhs_from: Option<SpannedValue<TypeList>>,
+    /// the implementation given to the macro, set when expansion begins
+    #[darling(skip)]
+    pub(crate) base: Option<Box<BinOpImpl>>,
+}
+
+/// Arguments for `sum(zero = "EXPR")`, the identity from which a derived `Sum` folds
+#[derive(Clone, FromMeta, Debug)]
+pub struct SumArgs {
+    #[darling(with = "expr_from_meta")]
+    pub zero: Box<Expr>,
+}
+
+/// Arguments for `product(one = "EXPR")`, the identity from which a derived `Product` folds
+#[derive(Clone, FromMeta, Debug)]
+pub struct ProductArgs {
+    #[darling(with = "expr_from_meta")]
+    pub one: Box<Expr>,
+}
+
+/// Whether `commute` first checks that the operation is commutative.
+///
+/// Given as the argument `commute` or `commute(unchecked)`.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum CommuteMode {
+    /// reject operations of the standard library which are not commutative; this is the default
+    #[default]
+    Checked,
+    /// commute any operation, for the rare case where `B op A` should really be `A op B`
+    Unchecked,
+}
+
+impl FromMeta for CommuteMode {
+    fn from_word() -> darling::Result<Self> {
+        Ok(CommuteMode::default())
+    }
+
+    fn from_list(items: &[NestedMeta]) -> darling::Result<Self> {
+        match items {
+            [] => Ok(CommuteMode::default()),
+            [NestedMeta::Meta(Meta::Path(path))] if path.is_ident("unchecked") => {
+                Ok(CommuteMode::Unchecked)
+            }
+            [item] => Err(darling::Error::custom("expected `unchecked`").with_span(item)),
+            [_, item, ..] => Err(darling::Error::too_many_items(1).with_span(item)),
+        }
+    }
+}
+
+/// A smart pointer through which a derived implementation takes an operand, as requested by
+/// the arguments `boxed`, `rc`, `arc`, and `cow`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SmartPointer {
+    /// `Box<T>`
+    Box,
+    /// `Rc<T>`
+    Rc,
+    /// `Arc<T>`
+    Arc,
+    /// `Cow<'_, T>`
+    Cow,
+}
+
+/// How a derived compound assignment `*self = *self op rhs` moves the value out of `*self`.
+///
+/// Given as the argument `assign`, `assign(copy)`, `assign(take)`, or
+/// `assign(replace = "EXPR")`.
+#[derive(Clone, Default, Debug)]
+pub enum AssignStrategy {
+    /// `*self`, for `Copy` types; this is the default
+    #[default]
+    Copy,
+    /// `core::mem::take(self)`, for `Default` types
+    Take,
+    /// `core::mem::replace(self, EXPR)`, leaving the placeholder `EXPR` in `self` meanwhile
+    Replace(Box<Expr>),
+}
+
+impl FromMeta for AssignStrategy {
+    fn from_word() -> darling::Result<Self> {
+        Ok(AssignStrategy::default())
+    }
+
+    fn from_list(items: &[NestedMeta]) -> darling::Result<Self> {
+        match items {
+            [] => Ok(AssignStrategy::default()),
+            [NestedMeta::Meta(Meta::Path(path))] if path.is_ident("copy") => {
+                Ok(AssignStrategy::Copy)
+            }
+            [NestedMeta::Meta(Meta::Path(path))] if path.is_ident("take") => {
+                Ok(AssignStrategy::Take)
+            }
+            [NestedMeta::Meta(Meta::NameValue(nv))] if nv.path.is_ident("replace") => {
+                expr_from_lit(&nv.lit).map(AssignStrategy::Replace)
+            }
+            [item] => Err(darling::Error::custom(
+                "expected one of `copy`, `take`, or `replace = \"..\"`",
+            )
+            .with_span(item)),
+            [_, item, ..] => Err(darling::Error::too_many_items(1).with_span(item)),
+        }
+    }
+}
+
+/// Arguments for `derive_div(recip = "path::to::fn")`, the reciprocal `fn(B) -> B` with which a
+/// derived `Div` multiplies
+#[derive(Clone, FromMeta, Debug)]
+pub struct DeriveDivArgs {
+    pub recip: Path,
+}
+
+/// A list of types, as in `rhs_types(f32, f64, "Vec<u8>")`.
+///
+/// Paths may be given as they are; any other type is given as a string literal.
+#[derive(Clone, Debug)]
+pub struct TypeList(pub Vec<Type>);
+
+impl FromMeta for TypeList {
+    fn from_list(items: &[NestedMeta]) -> darling::Result<Self> {
+        let mut types = Vec::new();
+        let mut errors = Vec::new();
+        for item in items {
+            match item {
+                NestedMeta::Meta(Meta::Path(path)) => types.push(parse_quote!(#path)),
+                NestedMeta::Lit(Lit::Str(lit)) => match lit.parse() {
+                    Ok(ty) => types.push(ty),
+                    Err(e) => errors.push(darling::Error::custom(e).with_span(lit)),
+                },
+                item => errors.push(darling::Error::custom("expected a type").with_span(item)),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(TypeList(types))
+        } else {
+            Err(darling::Error::multiple(errors))
+        }
+    }
 }
 
 /// Type definition for the output of a binary operation: `type Output = C;`
 #[derive(Clone, Debug)]
 pub struct BinOpOutput {
+    pub attrs: Vec<Attribute>,
     pub type_token: Token![type],
     pub ident: Ident,
     pub eq_token: Token![=],
@@ -40,16 +207,17 @@ pub struct BinOpFn {
     pub rhs_arg: FnArg,
     pub arrow_token: Token![->],
     pub out_ty: Type,
+    pub where_clause: Option<WhereClause>,
     pub block: Block,
 }
 
 /// An impl block for a binary operation.
 ///
-/// This represents a rather small subset of `syn::ItemImpl`, and is therefore
-/// very picky about what it will accept.
+/// This is built from a `syn::ItemImpl`, in which the output type and the
+/// operator method are found by their roles rather than their positions.
 ///
 /// Example:
-/// ```
+/// ```ignore
 /// impl Add<B> for A {
 ///     type Output = C;
 ///     
@@ -67,21 +235,29 @@ pub struct BinOpFn {
 /// In theory, generics can be freely used, or at least up to the same freedom
 /// as `syn::Generics` allows
 ///
-/// A feature of `syn::ItemImpl` which is lacking here is support for optional
-/// default and visibility keywords; these may be added later.
+/// The output type is the associated type `Output`, or else the only associated
+/// type; without any, it is inferred from the return type of the method. The
+/// operator method is the method taking `self` and one argument whose name is
+/// the lowercased trait name, or else the only such method. Any other items are
+/// kept in `items` and copied into derived implementations.
 #[derive(Clone, Debug)]
 pub struct BinOpImpl {
     pub attrs: Vec<Attribute>,
+    pub defaultness: Option<Token![default]>,
+    pub unsafety: Option<Token![unsafe]>,
     pub impl_token: Token![impl],
     pub generics: Generics,
     pub trait_: Path, // likely not the most general, should be its own thing
     pub lt_token: Option<Token![<]>,
     pub rhs_ty: Type,
+    /// any further generic arguments of the trait, following the rhs type
+    pub trait_args: Vec<GenericArgument>,
     pub for_token: Token![for],
     pub lhs_ty: Type,
     pub brace_token: token::Brace,
-    pub item_out: BinOpOutput,
+    pub item_out: Option<BinOpOutput>,
     pub item_fn: BinOpFn,
+    pub items: Vec<ImplItem>,
 }
 
 // impl Parse --------------------------------------------------------------------------------------
@@ -89,6 +265,7 @@ pub struct BinOpImpl {
 impl Parse for BinOpOutput {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         Ok(BinOpOutput {
+            attrs: input.call(Attribute::parse_outer)?,
             type_token: input.parse()?,
             ident: input.parse()?,
             eq_token: input.parse()?,
@@ -104,13 +281,14 @@ impl Parse for BinOpFn {
         Ok(BinOpFn {
             attrs: input.call(Attribute::parse_outer)?,
             fn_token: input.parse()?,
-            ident: input.parse()?,
+            ident: input.call(parse_fn_ident)?,
             paren_token: parenthesized!(content in input),
             lhs_arg: content.parse()?,
             comma_token: content.parse()?,
             rhs_arg: content.parse()?,
             arrow_token: input.parse()?,
             out_ty: input.parse()?,
+            where_clause: input.parse()?,
             block: input.parse()?,
         })
     }
@@ -118,56 +296,268 @@ impl Parse for BinOpFn {
 
 impl Parse for BinOpImpl {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        let attrs = input.call(Attribute::parse_outer)?;
-        let impl_token = input.parse()?;
+        BinOpImpl::try_from(input.parse::<ItemImpl>()?)
+    }
+}
 
-        let mut generics: Generics = input.parse()?;
+// impl TryFrom ------------------------------------------------------------------------------------
 
-        let trait_ = input.call(Path::parse_mod_style)?;
+impl TryFrom<ImplItemType> for BinOpOutput {
+    type Error = syn::Error;
 
-        let mut lt_token = None;
-        if input.peek(Token![<]) {
-            if input.peek2(Token![>]) {
-                let _: Token![<] = input.parse()?;
-                let _: Token![>] = input.parse()?;
-            } else {
-                lt_token = Some(input.parse()?);
-            }
+    fn try_from(item: ImplItemType) -> syn::Result<Self> {
+        if !item.generics.params.is_empty() {
+            return Err(syn::Error::new_spanned(
+                &item.generics,
+                "the output type takes no generic parameters",
+            ));
         }
 
-        let rhs_ty;
-        let for_token;
-        let lhs_ty: Type;
-        if lt_token.is_some() {
-            rhs_ty = input.parse()?;
-            let _: Token![>] = input.parse()?;
-            for_token = input.parse()?;
-            lhs_ty = input.parse()?;
-        } else {
-            for_token = input.parse()?;
-            lhs_ty = input.parse()?;
-            rhs_ty = lhs_ty.clone();
+        Ok(BinOpOutput {
+            attrs: item.attrs,
+            type_token: item.type_token,
+            ident: item.ident,
+            eq_token: item.eq_token,
+            ty: item.ty,
+            semi_token: item.semi_token,
+        })
+    }
+}
+
+impl TryFrom<ImplItemMethod> for BinOpFn {
+    type Error = syn::Error;
+
+    fn try_from(item: ImplItemMethod) -> syn::Result<Self> {
+        let sig = item.sig;
+        if !sig.generics.params.is_empty() {
+            return Err(syn::Error::new_spanned(
+                &sig.generics.params,
+                "operator methods take no generic parameters; declare them on the impl instead",
+            ));
         }
 
-        generics.where_clause = input.parse()?;
+        let mut inputs = sig.inputs.into_pairs();
+        let (lhs_arg, comma_token, rhs_arg) = match (inputs.next(), inputs.next(), inputs.next()) {
+            (Some(lhs_pair), Some(rhs_pair), None) => match lhs_pair.into_tuple() {
+                (FnArg::Receiver(lhs_arg), Some(comma_token)) => {
+                    (lhs_arg, comma_token, rhs_pair.into_value())
+                }
+                (lhs_arg, _) => {
+                    return Err(syn::Error::new_spanned(
+                        lhs_arg,
+                        "expected the receiver `self`",
+                    ))
+                }
+            },
+            _ => {
+                return Err(syn::Error::new(
+                    sig.paren_token.span,
+                    "expected `self` and one argument, as in `(self, rhs: B)`",
+                ))
+            }
+        };
+
+        let (arrow_token, out_ty) = match sig.output {
+            ReturnType::Type(arrow_token, out_ty) => (arrow_token, *out_ty),
+            ReturnType::Default => {
+                return Err(syn::Error::new(
+                    sig.paren_token.span,
+                    "expected the return type `-> Self::Output`",
+                ))
+            }
+        };
 
-        let content;
-        let brace_token = braced!(content in input);
-        let item_out = content.parse()?;
-        let item_fn = content.parse()?;
-
-        Ok(BinOpImpl {
-            attrs,
-            impl_token,
-            generics,
+        Ok(BinOpFn {
+            attrs: item.attrs,
+            fn_token: sig.fn_token,
+            ident: sig.ident,
+            paren_token: sig.paren_token,
+            lhs_arg,
+            comma_token,
+            rhs_arg,
+            arrow_token,
+            out_ty,
+            where_clause: sig.generics.where_clause,
+            block: item.block,
+        })
+    }
+}
+
+impl TryFrom<ItemImpl> for BinOpImpl {
+    type Error = syn::Error;
+
+    fn try_from(item: ItemImpl) -> syn::Result<Self> {
+        let (mut trait_, for_token) = match item.trait_ {
+            Some((None, trait_, for_token)) => (trait_, for_token),
+            Some((Some(bang), ..)) => {
+                return Err(syn::Error::new_spanned(
+                    bang,
+                    "negative impls are not supported",
+                ))
+            }
+            None => {
+                return Err(syn::Error::new_spanned(
+                    &item.self_ty,
+                    "expected an operator trait implementation, as in `impl Add<B> for A`",
+                ))
+            }
+        };
+        let lhs_ty = *item.self_ty;
+
+        let path_args = match trait_.segments.last_mut() {
+            Some(segment) => std::mem::replace(&mut segment.arguments, PathArguments::None),
+            None => PathArguments::None,
+        };
+        let (lt_token, rhs_ty, trait_args) = match path_args {
+            PathArguments::None => (None, lhs_ty.clone(), Vec::new()),
+            PathArguments::AngleBracketed(args) => {
+                let lt_token = args.lt_token;
+                let mut args = args.args.into_iter();
+                match args.next() {
+                    Some(GenericArgument::Type(rhs_ty)) => (Some(lt_token), rhs_ty, args.collect()),
+                    None => (None, lhs_ty.clone(), Vec::new()),
+                    Some(arg) => {
+                        return Err(syn::Error::new_spanned(
+                            arg,
+                            "expected the rhs type as the first argument, as in `Add<B>`",
+                        ))
+                    }
+                }
+            }
+            PathArguments::Parenthesized(args) => {
+                return Err(syn::Error::new_spanned(
+                    args,
+                    "expected the rhs type as the first argument, as in `Add<B>`",
+                ))
+            }
+        };
+
+        validate_kind(&trait_)?;
+
+        let op_fn = trait_
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string().to_lowercase())
+            .unwrap_or_default();
+        let brace_span = item.brace_token.span;
+        let mut items = item.items;
+
+        let out_pos = position_by_role(&items, |item| match item {
+            ImplItem::Type(item_out) => Some(item_out.ident == "Output"),
+            _ => None,
+        });
+        let item_out = match out_pos.map(|out_pos| items.remove(out_pos)) {
+            Some(ImplItem::Type(item_out)) => Some(BinOpOutput::try_from(item_out)?),
+            _ => None,
+        };
+
+        let fn_pos = position_by_role(&items, |item| match item {
+            ImplItem::Method(method)
+                if method.sig.inputs.len() == 2 && method.sig.receiver().is_some() =>
+            {
+                Some(method.sig.ident == op_fn)
+            }
+            _ => None,
+        })
+        .ok_or_else(|| {
+            syn::Error::new(
+                brace_span,
+                "expected the operator method, as in `fn add(self, rhs: B) -> Self::Output`",
+            )
+        })?;
+        let item_fn = match items.remove(fn_pos) {
+            ImplItem::Method(item_fn) => BinOpFn::try_from(item_fn)?,
+            _ => unreachable!(),
+        };
+        if item_out.is_none() && tokens_eq(&item_fn.out_ty, &quote!(Self::Output)) {
+            return Err(syn::Error::new_spanned(
+                &item_fn.out_ty,
+                "cannot infer `type Output` from `Self::Output`; declare it or return a concrete \
+                 type",
+            ));
+        }
+
+        let binop_impl = BinOpImpl {
+            attrs: item.attrs,
+            defaultness: item.defaultness,
+            unsafety: item.unsafety,
+            impl_token: item.impl_token,
+            generics: item.generics,
             trait_,
             lt_token,
             rhs_ty,
+            trait_args,
             for_token,
             lhs_ty,
-            brace_token,
+            brace_token: item.brace_token,
             item_out,
             item_fn,
-        })
+            items,
+        };
+        binop_impl.validate()?;
+
+        Ok(binop_impl)
     }
 }
+
+impl BinOpImpl {
+    /// Checks `self` against the [`catalog`] entry of its trait, if it has one, so that e.g.
+    /// `impl Add for A { fn sub(..) }` is reported at `sub`; custom traits always pass.
+    pub fn validate(&self) -> syn::Result<()> {
+        let info = match catalog::lookup(&self.trait_) {
+            Some(info) => info,
+            None => return Ok(()),
+        };
+        validate_kind(&self.trait_)?;
+
+        if self.item_fn.ident != info.method {
+            return Err(syn::Error::new_spanned(
+                &self.item_fn.ident,
+                format!(
+                    "`{}` is implemented by the method `{}`, found `{}`",
+                    info.trait_name, info.method, self.item_fn.ident,
+                ),
+            ));
+        }
+        if let Some(item_out) = self.item_out.as_ref().filter(|out| out.ident != "Output") {
+            return Err(syn::Error::new_spanned(
+                &item_out.ident,
+                format!(
+                    "`{}` names its output type `Output`, found `{}`",
+                    info.trait_name, item_out.ident,
+                ),
+            ));
+        }
+        if let Some(arg) = self.trait_args.first() {
+            return Err(syn::Error::new_spanned(
+                arg,
+                format!(
+                    "`{}` takes only the rhs type as an argument",
+                    info.trait_name
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks that the [`catalog`] entry of `trait_`, if it has one, is a binary operation, which
+/// parsing does first so that e.g. `impl Neg for A` is not reported for its method instead.
+fn validate_kind(trait_: &Path) -> syn::Result<()> {
+    let info = match catalog::lookup(trait_) {
+        Some(info) => info,
+        None => return Ok(()),
+    };
+
+    let reason = match info.kind {
+        OpKind::Binary => return Ok(()),
+        OpKind::Unary => "a unary operation; use `#[unop]` instead",
+        OpKind::Comparison => "a comparison, which `#[binop]` does not support",
+        OpKind::Index => "an indexing operation, which `#[binop]` does not support",
+    };
+    Err(syn::Error::new_spanned(
+        trait_,
+        format!("`{}` is {}", info.trait_name, reason),
+    ))
+}
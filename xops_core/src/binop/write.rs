@@ -1,22 +1,110 @@
 use proc_macro2::TokenStream;
-use quote::{quote, ToTokens, TokenStreamExt};
-use syn::parse_quote;
+use quote::{format_ident, quote, ToTokens, TokenStreamExt};
+use syn::{
+    parse_quote, Expr, FnArg, GenericParam, Ident, ItemImpl, Pat, PatType, Path, Type,
+    TypeReference,
+};
 
-use crate::{utils::TypeConversion, BinOpFn, BinOpImpl, BinOpOutput};
+use crate::{
+    catalog::{self, OpKind},
+    utils::{
+        add_bound, allow_named_lifetimes, fresh_lifetime, fresh_type_param, mentions_borrows,
+        mentions_lifetime, mentions_param, name_lifetime, named_lifetimes, placeholder,
+        prune_generics, resolve_self, substitute, tokens_eq, TypeConversion,
+    },
+    AssignOpImpl, AssignStrategy, BinOpFn, BinOpImpl, BinOpOutput, Result, SmartPointer,
+};
 
 impl BinOpImpl {
-    /// If `lhs_ty = &A`, this returns an implementation of `A op B` utilizing `&A op B`.
-    /// 
-    /// If `lhs_ty` is not a reference type, this returns `None`.
-    /// 
+    /// Returns `self` with each `Self` in the trait arguments, the argument `rhs`, the output
+    /// type, the where clause, and the extra items replaced by `lhs_ty`.
+    ///
+    /// A missing `type Output` is inferred from the return type of the method, which then
+    /// returns `Self::Output`.
+    ///
+    /// Derived implementations copy these tokens into impls whose `Self` is a different type, so
+    /// every derivation should start from a resolved implementation.
+    pub fn resolve_self(&self) -> Self {
+        let lhs_ty = &self.lhs_ty;
+        let mut resolved = self.clone();
+
+        resolved.rhs_ty = resolve_self(&self.rhs_ty, lhs_ty);
+        resolved.trait_args = self
+            .trait_args
+            .iter()
+            .map(|arg| resolve_self(arg, lhs_ty))
+            .collect();
+        resolved.item_fn.rhs_arg = resolve_self(&self.item_fn.rhs_arg, lhs_ty);
+        resolved.item_out = Some(match &self.item_out {
+            Some(item_out) => BinOpOutput {
+                ty: resolve_self(&item_out.ty, lhs_ty),
+                ..item_out.clone()
+            },
+            None => {
+                let out_ty = resolve_self(&self.item_fn.out_ty, lhs_ty);
+                resolved.item_fn.out_ty = parse_quote!(Self::Output);
+                parse_quote!(type Output = #out_ty;)
+            }
+        });
+        resolved.generics.where_clause = self
+            .generics
+            .where_clause
+            .as_ref()
+            .map(|where_clause| resolve_self(where_clause, lhs_ty));
+        resolved.items = self
+            .items
+            .iter()
+            .map(|item| resolve_self(item, lhs_ty))
+            .collect();
+
+        resolved
+    }
+
+    /// Returns a copy of `self`, fn body included, with each `placeholder` replaced by `ty`.
+    ///
+    /// If `placeholder` is a type parameter of `self`, it is dropped from the generics, its
+    /// bounds becoming bounds on `ty`.
+    pub fn instantiate(&self, placeholder: &Ident, ty: &Type) -> Result<Self> {
+        let mut generic = self.clone();
+        let mut bounds = Vec::new();
+        generic.generics.params = self
+            .generics
+            .params
+            .iter()
+            .filter(|param| match param {
+                GenericParam::Type(type_param) if type_param.ident == *placeholder => {
+                    bounds.extend(type_param.bounds.iter().cloned());
+                    false
+                }
+                _ => true,
+            })
+            .cloned()
+            .collect();
+        if !bounds.is_empty() {
+            generic
+                .generics
+                .make_where_clause()
+                .predicates
+                .push(parse_quote!(#placeholder: #(#bounds)+*));
+        }
+
+        Ok(substitute(&generic, &placeholder.to_string(), ty)?)
+    }
+
+    /// If `lhs_ty = &A`, this returns an implementation of `A op B` utilizing `&A op B`; for
+    /// `lhs_ty = &mut A`, the new method takes `mut self` and borrows it mutably instead.
+    ///
+    /// If `lhs_ty` is not a reference type, or the output type borrows from the reference, i.e.,
+    /// mentions its lifetime, this returns `None`.
+    ///
     /// In other words, if `self` is of the form
-    /// ```
+    /// ```ignore
     /// impl Op<B> for &A {
     ///     ...
     /// }
     /// ```
     /// then this produces the implementation
-    /// ```
+    /// ```ignore
     /// impl Op<B> for A {
     ///     ...
     ///     fn op(self, rhs: B) -> Self::Output {
@@ -25,34 +113,26 @@ impl BinOpImpl {
     /// }
     /// ```
     pub fn try_deref_lhs(&self) -> Option<Self> {
-        let lhs_ty = self.lhs_ty.as_deref()?;
-        let rhs_ty = &self.rhs_ty;
-        let fn_ident = &self.item_fn.ident;
-        let item_fn = parse_quote! {
-            fn #fn_ident(self, rhs: #rhs_ty) -> Self::Output {
-                (&self).#fn_ident(rhs)
-            }
-        };
+        let lhs = Recover::unborrow(&self.lhs_ty)?;
+        let rhs = (self.rhs_ty.clone(), Recover::Move);
 
-        Some(BinOpImpl {
-            lhs_ty,
-            item_fn,
-            ..self.clone()
-        })
+        self.recovered(lhs, rhs)
     }
 
-    /// If `rhs_ty = &B`, this returns an implementation of `A op B` utilizing `A op &B`.
-    /// 
-    /// If `rhs_ty` is not a reference type, this returns `None`.
-    /// 
+    /// If `rhs_ty = &B`, this returns an implementation of `A op B` utilizing `A op &B`; for
+    /// `rhs_ty = &mut B`, the new method takes `mut rhs` and borrows it mutably instead.
+    ///
+    /// If `rhs_ty` is not a reference type, or the output type borrows from the reference, i.e.,
+    /// mentions its lifetime, this returns `None`.
+    ///
     /// In other words, if `self` is of the form
-    /// ```
+    /// ```ignore
     /// impl Op<&B> for A {
     ///     ...
     /// }
     /// ```
     /// then this produces the implementation
-    /// ```
+    /// ```ignore
     /// impl Op<B> for A {
     ///     ...
     ///     fn op(self, rhs: B) -> Self::Output {
@@ -61,33 +141,26 @@ impl BinOpImpl {
     /// }
     /// ```
     pub fn try_deref_rhs(&self) -> Option<Self> {
-        let rhs_ty = self.rhs_ty.as_deref()?;
-        let fn_ident = &self.item_fn.ident;
-        let item_fn = parse_quote! {
-            fn #fn_ident(self, rhs: #rhs_ty) -> Self::Output {
-                self.#fn_ident(&rhs)
-            }
-        };
+        let lhs = (self.lhs_ty.clone(), Recover::Move);
+        let rhs = Recover::unborrow(&self.rhs_ty)?;
 
-        Some(BinOpImpl {
-            rhs_ty,
-            item_fn,
-            ..self.clone()
-        })
+        self.recovered(lhs, rhs)
     }
 
     /// If `lhs_ty = &A` and `rhs_ty = &B`, this returns an implementation of `A op B` utilizing `&A op &B`.
-    /// 
-    /// If `lhs_ty` and `rhs_ty` not a references type, this returns `None`.
-    /// 
+    /// Mutable references are borrowed mutably, as in [`BinOpImpl::try_deref_lhs`].
+    ///
+    /// If `lhs_ty` and `rhs_ty` not a references type, or the output type borrows from either
+    /// reference, this returns `None`.
+    ///
     /// In other words, if `self` is of the form
-    /// ```
+    /// ```ignore
     /// impl Op<&B> for &A {
     ///     ...
     /// }
     /// ```
     /// then this produces the implementation
-    /// ```
+    /// ```ignore
     /// impl Op<B> for A {
     ///     ...
     ///     fn op(self, rhs: B) -> Self::Output {
@@ -96,35 +169,25 @@ impl BinOpImpl {
     /// }
     /// ```
     pub fn try_deref_both(&self) -> Option<Self> {
-        let lhs_ty = self.lhs_ty.as_deref()?;
-        let rhs_ty = self.rhs_ty.as_deref()?;
-        let fn_ident = &self.item_fn.ident;
-        let item_fn = parse_quote! {
-            fn #fn_ident(self, rhs: #rhs_ty) -> Self::Output {
-                (&self).#fn_ident(&rhs)
-            }
-        };
+        let lhs = Recover::unborrow(&self.lhs_ty)?;
+        let rhs = Recover::unborrow(&self.rhs_ty)?;
 
-        Some(BinOpImpl {
-            lhs_ty,
-            rhs_ty,
-            item_fn,
-            ..self.clone()
-        })
+        self.recovered(lhs, rhs)
     }
 
     /// Returns an implementation of `&A op B` utilizing `A op B`.
-    /// 
-    /// The macro user must enure that `A: Clone`.
-    /// 
+    ///
+    /// This needs `A: Clone`, which is added to the where clause if `A` involves generic
+    /// parameters.
+    ///
     /// In other words, if `self` is of the form
-    /// ```
+    /// ```ignore
     /// impl Op<B> for A {
     ///     ...
     /// }
     /// ```
     /// then this produces the implementation
-    /// ```
+    /// ```ignore
     /// impl Op<B> for &A {
     ///     ...
     ///     fn op(self, rhs: B) -> Self::Output {
@@ -133,35 +196,26 @@ impl BinOpImpl {
     /// }
     /// ```
     pub fn ref_lhs_clone(&self) -> Self {
-        let fn_ident = &self.item_fn.ident;
         let lhs_ty = self.lhs_ty.as_ref();
-        let rhs_ty = &self.rhs_ty;
+        let rhs_ty = self.rhs_ty.clone();
 
-        let item_fn = parse_quote! {
-            fn #fn_ident(self, rhs: #rhs_ty) -> Self::Output {
-                self.clone().#fn_ident(rhs)
-            }
-        };
-
-        BinOpImpl {
-            lhs_ty,
-            item_fn,
-            ..self.clone()
-        }
+        self.delegate(lhs_ty, rhs_ty, quote!(self.clone()), quote!(rhs))
+            .with_clone_bound(&self.lhs_ty)
     }
 
     /// Returns an implementation of `A op &B` utilizing `A op B`.
-    /// 
-    /// The macro user must enure that `B: Clone`.
-    /// 
+    ///
+    /// This needs `B: Clone`, which is added to the where clause if `B` involves generic
+    /// parameters.
+    ///
     /// In other words, if `self` is of the form
-    /// ```
+    /// ```ignore
     /// impl Op<B> for A {
     ///     ...
     /// }
     /// ```
     /// then this produces the implementation
-    /// ```
+    /// ```ignore
     /// impl Op<&B> for A {
     ///     ...
     ///     fn op(self, rhs: B) -> Self::Output {
@@ -170,34 +224,26 @@ impl BinOpImpl {
     /// }
     /// ```
     pub fn ref_rhs_clone(&self) -> Self {
-        let fn_ident = &self.item_fn.ident;
+        let lhs_ty = self.lhs_ty.clone();
         let rhs_ty = self.rhs_ty.as_ref();
 
-        let item_fn = parse_quote! {
-            fn #fn_ident(self, rhs: #rhs_ty) -> Self::Output {
-                self.#fn_ident(rhs.clone())
-            }
-        };
-
-        BinOpImpl {
-            rhs_ty,
-            item_fn,
-            ..self.clone()
-        }
+        self.delegate(lhs_ty, rhs_ty, quote!(self), quote!(rhs.clone()))
+            .with_clone_bound(&self.rhs_ty)
     }
 
     /// Returns an implementation of `&A op &B` utilizing `A op B`.
-    /// 
-    /// The macro user must enure that `A: Clone` and `B: Clone`.
-    /// 
+    ///
+    /// This needs `A: Clone` and `B: Clone`, which are added to the where clause for whichever
+    /// of them involve generic parameters.
+    ///
     /// In other words, if `self` is of the form
-    /// ```
+    /// ```ignore
     /// impl Op<B> for A {
     ///     ...
     /// }
     /// ```
     /// then this produces the implementation
-    /// ```
+    /// ```ignore
     /// impl Op<&B> for &A {
     ///     ...
     ///     fn op(self, rhs: B) -> Self::Output {
@@ -206,36 +252,153 @@ impl BinOpImpl {
     /// }
     /// ```
     pub fn ref_both_clone(&self) -> Self {
-        let fn_ident = &self.item_fn.ident;
         let lhs_ty = self.lhs_ty.as_ref();
         let rhs_ty = self.rhs_ty.as_ref();
 
-        let item_fn = parse_quote! {
-            fn #fn_ident(self, rhs: #rhs_ty) -> Self::Output {
-                self.clone().#fn_ident(rhs.clone())
-            }
-        };
+        self.delegate(lhs_ty, rhs_ty, quote!(self.clone()), quote!(rhs.clone()))
+            .with_clone_bound(&self.lhs_ty)
+            .with_clone_bound(&self.rhs_ty)
+    }
 
-        BinOpImpl {
-            lhs_ty,
-            rhs_ty,
-            item_fn,
-            ..self.clone()
-        }
+    /// Returns an implementation of `&A op B` utilizing `A op B`.
+    ///
+    /// This needs `A: Copy`, which is added to the where clause if `A` involves generic
+    /// parameters.
+    ///
+    /// In other words, if `self` is of the form
+    /// ```ignore
+    /// impl Op<B> for A {
+    ///     ...
+    /// }
+    /// ```
+    /// then this produces the implementation
+    /// ```ignore
+    /// impl Op<B> for &A {
+    ///     ...
+    ///     fn op(self, rhs: B) -> Self::Output {
+    ///         (*self).op(rhs)
+    ///     }
+    /// }
+    /// ```
+    pub fn ref_lhs_copy(&self) -> Self {
+        let lhs_ty = self.lhs_ty.as_ref();
+        let rhs_ty = self.rhs_ty.clone();
+
+        self.delegate(lhs_ty, rhs_ty, quote!((*self)), quote!(rhs))
+            .with_copy_bound(&self.lhs_ty)
     }
 
-    /// Returns an implementation of `B op A` utilizing `A op B`.
-    /// 
-    /// The macro user must enure that some `impl Op<A> for B` does not exist elsewhere.
-    /// 
+    /// Returns an implementation of `A op &B` utilizing `A op B`.
+    ///
+    /// This needs `B: Copy`, which is added to the where clause if `B` involves generic
+    /// parameters.
+    ///
     /// In other words, if `self` is of the form
+    /// ```ignore
+    /// impl Op<B> for A {
+    ///     ...
+    /// }
     /// ```
+    /// then this produces the implementation
+    /// ```ignore
+    /// impl Op<&B> for A {
+    ///     ...
+    ///     fn op(self, rhs: &B) -> Self::Output {
+    ///         self.op(*rhs)
+    ///     }
+    /// }
+    /// ```
+    pub fn ref_rhs_copy(&self) -> Self {
+        let lhs_ty = self.lhs_ty.clone();
+        let rhs_ty = self.rhs_ty.as_ref();
+
+        self.delegate(lhs_ty, rhs_ty, quote!(self), quote!(*rhs))
+            .with_copy_bound(&self.rhs_ty)
+    }
+
+    /// Returns an implementation of `&A op &B` utilizing `A op B`.
+    ///
+    /// This needs `A: Copy` and `B: Copy`, which are added to the where clause for whichever of
+    /// them involve generic parameters.
+    ///
+    /// In other words, if `self` is of the form
+    /// ```ignore
     /// impl Op<B> for A {
     ///     ...
     /// }
     /// ```
     /// then this produces the implementation
+    /// ```ignore
+    /// impl Op<&B> for &A {
+    ///     ...
+    ///     fn op(self, rhs: &B) -> Self::Output {
+    ///         (*self).op(*rhs)
+    ///     }
+    /// }
+    /// ```
+    pub fn ref_both_copy(&self) -> Self {
+        let lhs_ty = self.lhs_ty.as_ref();
+        let rhs_ty = self.rhs_ty.as_ref();
+
+        self.delegate(lhs_ty, rhs_ty, quote!((*self)), quote!(*rhs))
+            .with_copy_bound(&self.lhs_ty)
+            .with_copy_bound(&self.rhs_ty)
+    }
+
+    /// Returns an implementation of `C op B` utilizing `A op B` and `A: From<C>`.
+    ///
+    /// In other words, if `self` is of the form
+    /// ```ignore
+    /// impl Op<B> for A {
+    ///     ...
+    /// }
+    /// ```
+    /// then this produces the implementation
+    /// ```ignore
+    /// impl Op<B> for C {
+    ///     ...
+    ///     fn op(self, rhs: B) -> Self::Output {
+    ///         <A as From<C>>::from(self).op(rhs)
+    ///     }
+    /// }
+    /// ```
+    pub fn lhs_from(&self, ty: &Type) -> Self {
+        self.converted(Some(ty), None)
+    }
+
+    /// Returns an implementation of `A op C` utilizing `A op B` and `B: From<C>`.
+    ///
+    /// In other words, if `self` is of the form
+    /// ```ignore
+    /// impl Op<B> for A {
+    ///     ...
+    /// }
     /// ```
+    /// then this produces the implementation
+    /// ```ignore
+    /// impl Op<C> for A {
+    ///     ...
+    ///     fn op(self, rhs: C) -> Self::Output {
+    ///         self.op(<B as From<C>>::from(rhs))
+    ///     }
+    /// }
+    /// ```
+    pub fn rhs_from(&self, ty: &Type) -> Self {
+        self.converted(None, Some(ty))
+    }
+
+    /// Returns an implementation of `B op A` utilizing `A op B`.
+    ///
+    /// The macro user must ensure that some `impl Op<A> for B` does not exist elsewhere.
+    ///
+    /// In other words, if `self` is of the form
+    /// ```ignore
+    /// impl Op<B> for A {
+    ///     ...
+    /// }
+    /// ```
+    /// then this produces the implementation
+    /// ```ignore
     /// impl Op<A> for B {
     ///     ...
     ///     fn op(self, rhs: A) -> Self::Output {
@@ -247,19 +410,933 @@ impl BinOpImpl {
         let lhs_ty = self.rhs_ty.clone();
         let rhs_ty = self.lhs_ty.clone();
 
+        self.delegate(lhs_ty, rhs_ty, quote!(rhs), quote!(self))
+    }
+
+    /// Returns an implementation of `B op A` utilizing `A op B` and anti-commutativity, i.e.,
+    /// `b op a = -(a op b)`, as holds for e.g. `Sub`.
+    ///
+    /// The macro user must ensure that the output type `C` satisfies `C: Neg<Output = C>`; if
+    /// `C` involves generic parameters, this bound is added to the where clause.
+    ///
+    /// In other words, if `self` is of the form
+    /// ```ignore
+    /// impl Op<B> for A {
+    ///     ...
+    /// }
+    /// ```
+    /// then this produces the implementation
+    /// ```ignore
+    /// impl Op<A> for B {
+    ///     ...
+    ///     fn op(self, rhs: A) -> Self::Output {
+    ///         -rhs.op(self)
+    ///     }
+    /// }
+    /// ```
+    pub fn anticommute(&self) -> Self {
+        let lhs_ty = self.rhs_ty.clone();
+        let rhs_ty = self.lhs_ty.clone();
         let fn_ident = &self.item_fn.ident;
+
+        let mut derived = self.delegate_with(lhs_ty, rhs_ty, quote!(-rhs.#fn_ident(self)));
+        let out_ty = self.output_ty();
+        let neg_bound = quote!(::core::ops::Neg<Output = #out_ty>);
+        add_bound(&mut derived.generics, out_ty, neg_bound);
+        derived
+    }
+
+    /// Returns an implementation of `B op A` utilizing the function `commute_fn: fn(B, A) -> C`,
+    /// for operations whose commutation is neither trivial nor anti-commutative.
+    ///
+    /// In other words, if `self` is of the form
+    /// ```ignore
+    /// impl Op<B> for A {
+    ///     ...
+    /// }
+    /// ```
+    /// then this produces the implementation
+    /// ```ignore
+    /// impl Op<A> for B {
+    ///     ...
+    ///     fn op(self, rhs: A) -> Self::Output {
+    ///         commute_fn(self, rhs)
+    ///     }
+    /// }
+    /// ```
+    pub fn commute_with(&self, commute_fn: &Path) -> Self {
+        let lhs_ty = self.rhs_ty.clone();
+        let rhs_ty = self.lhs_ty.clone();
+
+        self.delegate_with(lhs_ty, rhs_ty, quote!(#commute_fn(self, rhs)))
+    }
+
+    /// If `self` implements `Add`, this returns an implementation of `A - B` utilizing `A + B`.
+    ///
+    /// If `trait_` is not `Add`, this returns `None`.
+    ///
+    /// The macro user must ensure that `B: Neg<Output = B>`; if `B` involves generic parameters,
+    /// this bound is added to the where clause.
+    ///
+    /// In other words, if `self` is of the form
+    /// ```ignore
+    /// impl Add<B> for A {
+    ///     ...
+    /// }
+    /// ```
+    /// then this produces the implementation
+    /// ```ignore
+    /// impl Sub<B> for A {
+    ///     ...
+    ///     fn sub(self, rhs: B) -> Self::Output {
+    ///         self.add(-rhs)
+    ///     }
+    /// }
+    /// ```
+    pub fn sub_from_add(&self) -> Option<Self> {
+        if self.op_ident()? != "Add" {
+            return None;
+        }
+
+        let lhs_ty = self.lhs_ty.clone();
+        let rhs_ty = self.rhs_ty.clone();
+
+        let mut derived = self
+            .delegate(lhs_ty, rhs_ty, quote!(self), quote!(-rhs))
+            .with_op(parse_quote!(::core::ops::Sub), parse_quote!(sub));
+        let neg_ty = &self.rhs_ty;
+        let neg_bound = quote!(::core::ops::Neg<Output = #neg_ty>);
+        add_bound(&mut derived.generics, &self.rhs_ty, neg_bound);
+        Some(derived)
+    }
+
+    /// If `self` implements `Mul`, this returns an implementation of `A / B` utilizing `A * B`
+    /// and the reciprocal function `recip: fn(B) -> B`.
+    ///
+    /// If `trait_` is not `Mul`, this returns `None`.
+    ///
+    /// In other words, if `self` is of the form
+    /// ```ignore
+    /// impl Mul<B> for A {
+    ///     ...
+    /// }
+    /// ```
+    /// then this produces the implementation
+    /// ```ignore
+    /// impl Div<B> for A {
+    ///     ...
+    ///     fn div(self, rhs: B) -> Self::Output {
+    ///         self.mul(recip(rhs))
+    ///     }
+    /// }
+    /// ```
+    pub fn div_from_mul(&self, recip: &Path) -> Option<Self> {
+        if self.op_ident()? != "Mul" {
+            return None;
+        }
+
+        let lhs_ty = self.lhs_ty.clone();
+        let rhs_ty = self.rhs_ty.clone();
+
+        let derived = self
+            .delegate(lhs_ty, rhs_ty, quote!(self), quote!(#recip(rhs)))
+            .with_op(parse_quote!(::core::ops::Div), parse_quote!(div));
+        Some(derived)
+    }
+
+    /// Returns an implementation of `A op= B` utilizing `A op B`.
+    ///
+    /// If `lhs_ty` is a reference type or the output type is not `lhs_ty`, this returns `None`.
+    ///
+    /// The ten binary operations of `std::ops` are mapped to their `*Assign` traits in
+    /// `::core::ops`, whereas any other trait `path::to::Op` is mapped to `path::to::OpAssign`
+    /// with the method `op_assign`, as generated for traits defined with `#[op_trait]`.
+    ///
+    /// The value of `*self` is moved out according to `strategy`; for `Copy` and `Take`, the
+    /// macro user must ensure that `A: Copy` or `A: Default` respectively, and the bound is added
+    /// to the where clause if `A` involves generic parameters.
+    ///
+    /// In other words, if `self` is of the form
+    /// ```ignore
+    /// impl Op<B> for A {
+    ///     type Output = A;
+    ///     ...
+    /// }
+    /// ```
+    /// then this produces the implementation (for the `Copy` strategy)
+    /// ```ignore
+    /// impl OpAssign<B> for A {
+    ///     fn op_assign(&mut self, rhs: B) {
+    ///         *self = Op::op(*self, rhs);
+    ///     }
+    /// }
+    /// ```
+    pub fn assign(&self, strategy: &AssignStrategy) -> Option<AssignOpImpl> {
+        if self.lhs_ty.as_deref().is_some() || !self.outputs_lhs() {
+            return None;
+        }
+
+        let assign_trait = match catalog::lookup(&self.trait_) {
+            Some(info) => info.assign_path()?,
+            None => {
+                let mut assign_trait = self.trait_.clone();
+                assign_trait.segments.last_mut()?.ident =
+                    format_ident!("{}Assign", self.op_ident()?);
+                assign_trait
+            }
+        };
+
+        let trait_ = &self.trait_;
+        let fn_ident = &self.item_fn.ident;
+        let assign_fn = format_ident!("{}_assign", fn_ident);
+        let lhs_ty = &self.lhs_ty;
+        let rhs_ty = &self.rhs_ty;
+        let trait_args = &self.trait_args;
+        let attrs = &self.attrs;
+
+        let mut generics = self.generics.clone();
+        let taken = match strategy {
+            AssignStrategy::Copy => {
+                add_bound(&mut generics, lhs_ty, quote!(::core::marker::Copy));
+                quote!(*self)
+            }
+            AssignStrategy::Take => {
+                add_bound(&mut generics, lhs_ty, quote!(::core::default::Default));
+                quote!(::core::mem::take(self))
+            }
+            AssignStrategy::Replace(placeholder) => {
+                quote!(::core::mem::replace(self, #placeholder))
+            }
+        };
+        let where_clause = &generics.where_clause;
+
+        Some(parse_quote! {
+            #(#attrs)*
+            impl #generics #assign_trait<#rhs_ty #(, #trait_args)*> for #lhs_ty #where_clause {
+                fn #assign_fn(&mut self, rhs: #rhs_ty) {
+                    *self = #trait_::#fn_ident(#taken, rhs);
+                }
+            }
+        })
+    }
+
+    /// Returns an implementation of `core::iter::Sum` for `A` utilizing `A + A` or `A + &A`,
+    /// folding from the identity `zero`; see [`BinOpImpl::fold`].
+    pub fn sum(&self, zero: &Expr) -> Option<ItemImpl> {
+        self.fold("Add", quote!(::core::iter::Sum), quote!(sum), zero)
+    }
+
+    /// Returns an implementation of `core::iter::Product` for `A` utilizing `A * A` or `A * &A`,
+    /// folding from the identity `one`; see [`BinOpImpl::fold`].
+    pub fn product(&self, one: &Expr) -> Option<ItemImpl> {
+        self.fold("Mul", quote!(::core::iter::Product), quote!(product), one)
+    }
+
+    /// Returns an implementation of the iterator folding trait `fold_trait` for `A`, if `self`
+    /// is of the form `impl Op<A> for A` or `impl Op<&A> for A` with output type `A`, and the
+    /// trait name of `self` is `op_name`; otherwise this returns `None`.
+    ///
+    /// In other words, if `self` is of the form
+    /// ```ignore
+    /// impl Op<&A> for A {
+    ///     type Output = A;
+    ///     ...
+    /// }
+    /// ```
+    /// then this produces the implementation
+    /// ```ignore
+    /// impl<'a> Fold<&'a A> for A {
+    ///     fn fold<I: Iterator<Item = &'a A>>(iter: I) -> Self {
+    ///         iter.fold(init, |acc, item| Op::op(acc, item))
+    ///     }
+    /// }
+    /// ```
+    pub fn fold(
+        &self,
+        op_name: &str,
+        fold_trait: TokenStream,
+        fold_fn: TokenStream,
+        init: &Expr,
+    ) -> Option<ItemImpl> {
+        if self.op_ident()? != op_name || !self.outputs_lhs() {
+            return None;
+        }
+
+        let lhs_ty = &self.lhs_ty;
+        let mut generics = self.generics.clone();
+        let item_ty: Type = if tokens_eq(&self.rhs_ty, lhs_ty) {
+            lhs_ty.clone()
+        } else if !self.rhs_ty.is_mut_ref() && tokens_eq(&self.rhs_ty.as_deref()?, lhs_ty) {
+            let lifetime = fresh_lifetime(&generics, "a");
+            generics.params.insert(0, parse_quote!(#lifetime));
+            parse_quote!(&#lifetime #lhs_ty)
+        } else {
+            return None;
+        };
+
+        let trait_ = &self.trait_;
+        let fn_ident = &self.item_fn.ident;
+        let iter_ty = fresh_type_param(&generics, "I");
+        let attrs = &self.attrs;
+        let where_clause = &generics.where_clause;
+
+        Some(parse_quote! {
+            #(#attrs)*
+            impl #generics #fold_trait<#item_ty> for #lhs_ty #where_clause {
+                fn #fold_fn<#iter_ty>(iter: #iter_ty) -> Self
+                where
+                    #iter_ty: ::core::iter::Iterator<Item = #item_ty>,
+                {
+                    iter.fold(#init, |acc, item| #trait_::#fn_ident(acc, item))
+                }
+            }
+        })
+    }
+
+    /// Returns implementations of the three other ownership forms of `self`, i.e., all of
+    /// `A op B`, `&A op B`, `A op &B`, and `&A op &B` except the one `self` already is.
+    ///
+    /// Each operand of a derived implementation is recovered from the base operand by
+    /// borrowing, if the base takes a reference, or by cloning, if the base takes it by value.
+    /// See [`BinOpImpl::ref_forms_copy`] to copy rather than clone.
+    ///
+    /// Any operand recovered by cloning needs to be `Clone`, which is added to the where clause
+    /// if the operand type involves generic parameters. Forms whose output type would borrow
+    /// from a dropped reference are left out.
+    ///
+    /// For example, if `self` is of the form
+    /// ```ignore
+    /// impl Op<&B> for A {
+    ///     ...
+    /// }
+    /// ```
+    /// then the implementation produced for `&A op B` is
+    /// ```ignore
+    /// impl Op<B> for &A {
+    ///     ...
+    ///     fn op(self, rhs: B) -> Self::Output {
+    ///         self.clone().op(&rhs)
+    ///     }
+    /// }
+    /// ```
+    pub fn ref_forms(&self) -> Vec<Self> {
+        self.ref_forms_by(Recover::Clone)
+    }
+
+    /// Same as [`BinOpImpl::ref_forms`], but any operand the base takes by value is recovered
+    /// by dereferencing instead of cloning, e.g., `(*self).op(&rhs)`.
+    ///
+    /// Any operand recovered this way needs to be `Copy`, which is added to the where clause if
+    /// the operand type involves generic parameters.
+    pub fn ref_forms_copy(&self) -> Vec<Self> {
+        self.ref_forms_by(Recover::Copy)
+    }
+
+    /// shared implementation of `ref_forms` and `ref_forms_copy`
+    fn ref_forms_by(&self, owned: Recover) -> Vec<Self> {
+        let base_form = (
+            self.lhs_ty.as_deref().is_some(),
+            self.rhs_ty.as_deref().is_some(),
+        );
+
+        [(false, false), (true, false), (false, true), (true, true)]
+            .iter()
+            .filter(|&&form| form != base_form)
+            .filter_map(|&(lhs_ref, rhs_ref)| {
+                let lhs = Recover::reshape(&self.lhs_ty, lhs_ref, owned);
+                let rhs = Recover::reshape(&self.rhs_ty, rhs_ref, owned);
+                self.recovered(lhs, rhs)
+            })
+            .collect()
+    }
+
+    /// Returns implementations of `self` with its shared reference operands exchanged for
+    /// mutable ones, i.e., `&mut A op B`, `A op &mut B`, and `&mut A op &mut B` for
+    /// `&A op &B`, each reborrowing its mutable operands.
+    ///
+    /// In other words, if `self` is of the form
+    /// ```ignore
+    /// impl Op<B> for &A {
+    ///     ...
+    /// }
+    /// ```
+    /// then this produces the implementation
+    /// ```ignore
+    /// impl Op<B> for &mut A {
+    ///     ...
+    ///     fn op(self, rhs: B) -> Self::Output {
+    ///         (&*self).op(rhs)
+    ///     }
+    /// }
+    /// ```
+    pub fn mut_ref_forms(&self) -> Vec<Self> {
+        let reborrow = |ty: &Type| match ty {
+            Type::Reference(ref_ty) if ref_ty.mutability.is_none() => {
+                let lifetime = &ref_ty.lifetime;
+                let elem = &ref_ty.elem;
+                Some((parse_quote!(&#lifetime mut #elem), Recover::Reborrow))
+            }
+            _ => None,
+        };
+        let lhs_mut = reborrow(&self.lhs_ty);
+        let rhs_mut = reborrow(&self.rhs_ty);
+        let lhs = (self.lhs_ty.clone(), Recover::Move);
+        let rhs = (self.rhs_ty.clone(), Recover::Move);
+
+        [
+            lhs_mut.clone().map(|lhs_mut| (lhs_mut, rhs.clone())),
+            rhs_mut.clone().map(|rhs_mut| (lhs, rhs_mut)),
+            lhs_mut.zip(rhs_mut),
+        ]
+        .iter()
+        .flatten()
+        .filter_map(|(lhs, rhs)| self.recovered(lhs.clone(), rhs.clone()))
+        .collect()
+    }
+
+    /// Returns implementations of `self` taking one of its operands through the smart pointer
+    /// `pointer` instead, i.e., `P<A> op B` and `A op P<B>` for `A op B`.
+    ///
+    /// An operand which the base takes by shared reference is reborrowed out of the pointer. One
+    /// taken by value is moved out of a `Box`, unwrapped from an `Rc` or `Arc` unless it is
+    /// shared, in which case it is cloned, and taken out of a `Cow` with `into_owned`; the latter
+    /// three need the operand to be `Clone`, which is added to the where clause if the operand
+    /// type involves generic parameters. Operands taken by mutable reference are left as they
+    /// are.
+    ///
+    /// Only `Box` is `#[fundamental]`, so for the traits of `std::ops`, the orphan rule forbids
+    /// wrapping one operand into an `Rc`, `Arc`, or `Cow` unless the other is local; such forms
+    /// are left out if the other operand is a primitive type or a type parameter. Forms whose
+    /// output type would mention a lifetime which only a removed reference constrains are left
+    /// out as well.
+    ///
+    /// In other words, if `self` is of the form
+    /// ```ignore
+    /// impl Op<&B> for A {
+    ///     ...
+    /// }
+    /// ```
+    /// then this produces the implementations (for `Rc`)
+    /// ```ignore
+    /// impl Op<&B> for Rc<A> {
+    ///     ...
+    ///     fn op(self, rhs: &B) -> Self::Output {
+    ///         Rc::try_unwrap(self).unwrap_or_else(|rc| (*rc).clone()).op(rhs)
+    ///     }
+    /// }
+    ///
+    /// impl Op<Rc<B>> for A {
+    ///     ...
+    ///     fn op(self, rhs: Rc<B>) -> Self::Output {
+    ///         self.op(&*rhs)
+    ///     }
+    /// }
+    /// ```
+    pub fn pointer_forms(&self, pointer: SmartPointer) -> Vec<Self> {
+        let wrap = |ty: &Type| match ty {
+            Type::Reference(ref_ty) if ref_ty.mutability.is_some() => None,
+            Type::Reference(ref_ty) => Some((pointer.wrap(&ref_ty.elem), Recover::Reborrow)),
+            _ => Some((pointer.wrap(ty), Recover::Unwrap(pointer))),
+        };
+        let orphaned = |other: &Type| {
+            !pointer.is_fundamental() && self.is_std_op() && self.foreign_operand(other)
+        };
+        let lhs = (self.lhs_ty.clone(), Recover::Move);
+        let rhs = (self.rhs_ty.clone(), Recover::Move);
+
+        let lhs_form = wrap(&self.lhs_ty)
+            .filter(|_| !orphaned(&self.rhs_ty))
+            .map(|lhs_ptr| (lhs_ptr, rhs));
+        let rhs_form = wrap(&self.rhs_ty)
+            .filter(|_| !orphaned(&self.lhs_ty))
+            .map(|rhs_ptr| (lhs, rhs_ptr));
+
+        [lhs_form, rhs_form]
+            .iter()
+            .flatten()
+            .filter_map(|(lhs, rhs)| self.recovered(lhs.clone(), rhs.clone()))
+            .collect()
+    }
+
+    /// Returns an implementation of `lhs_ty op rhs_ty` which recovers the operands of `self`
+    /// from its own as given by `lhs_from` and `rhs_from`.
+    ///
+    /// Operands recovered by copying get a `Copy` bound if their type involves generic
+    /// parameters, likewise operands recovered by cloning get a `Clone` bound, and
+    /// operands borrowed mutably are bound with `mut`.
+    ///
+    /// If the output type borrows from an operand which the new method owns, as for
+    /// `type Output = Thing<'a>` once `&'a A` is dereferenced to `A`, this returns `None`; rustc
+    /// rejects such implementations, either for the unconstrained lifetime or for returning a
+    /// reference to a local.
+    fn recovered(
+        &self,
+        (lhs_ty, lhs_from): (Type, Recover),
+        (rhs_ty, rhs_from): (Type, Recover),
+    ) -> Option<Self> {
+        let output = self.output_ty().to_token_stream();
+        let borrows_local = |base_ty: &Type, ty: &Type, from: Recover| {
+            match (base_ty, from) {
+                (Type::Reference(ref_ty), Recover::Borrow | Recover::BorrowMut) => {
+                    ref_ty.lifetime.as_ref()
+                }
+                (Type::Reference(ref_ty), Recover::Reborrow) if ty.as_deref().is_none() => {
+                    ref_ty.lifetime.as_ref()
+                }
+                _ => None,
+            }
+            .is_some_and(|lifetime| mentions_lifetime(output.clone(), &lifetime.ident))
+        };
+        if borrows_local(&self.lhs_ty, &lhs_ty, lhs_from)
+            || borrows_local(&self.rhs_ty, &rhs_ty, rhs_from)
+        {
+            return None;
+        }
+
+        let mut derived = self.delegate(
+            lhs_ty,
+            rhs_ty,
+            lhs_from.receiver(quote!(self)),
+            rhs_from.argument(quote!(rhs)),
+        );
+        if lhs_from == Recover::BorrowMut {
+            derived.item_fn.lhs_arg.mutability = Some(Default::default());
+        }
+        if rhs_from == Recover::BorrowMut {
+            if let FnArg::Typed(PatType { pat, .. }) = &mut derived.item_fn.rhs_arg {
+                if let Pat::Ident(pat_ident) = pat.as_mut() {
+                    pat_ident.mutability = Some(Default::default());
+                }
+            }
+        }
+        if lhs_from == Recover::Copy {
+            derived = derived.with_copy_bound(&self.lhs_ty);
+        }
+        if rhs_from == Recover::Copy {
+            derived = derived.with_copy_bound(&self.rhs_ty);
+        }
+        if lhs_from.clones() {
+            derived = derived.with_clone_bound(&self.lhs_ty);
+        }
+        if rhs_from.clones() {
+            derived = derived.with_clone_bound(&self.rhs_ty);
+        }
+        (!derived.output_unconstrained()).then_some(derived)
+    }
+
+    /// Returns an implementation whose operands are converted from `lhs_from` and `rhs_from`, where
+    /// given, into the operand types of `self`.
+    fn converted(&self, lhs_from: Option<&Type>, rhs_from: Option<&Type>) -> Self {
+        let mut bounds = Vec::new();
+        let mut convert = |ty: &Type, from: Option<&Type>, expr: TokenStream| match from {
+            Some(from) => {
+                bounds.push((ty.clone(), quote!(::core::convert::From<#from>)));
+                (
+                    from.clone(),
+                    quote!(<#ty as ::core::convert::From<#from>>::from(#expr)),
+                )
+            }
+            None => (ty.clone(), expr),
+        };
+
+        let (lhs_ty, lhs_expr) = convert(&self.lhs_ty, lhs_from, quote!(self));
+        let (rhs_ty, rhs_expr) = convert(&self.rhs_ty, rhs_from, quote!(rhs));
+
+        let mut derived = self.delegate(lhs_ty, rhs_ty, lhs_expr, rhs_expr);
+        for (ty, bound) in bounds {
+            add_bound(&mut derived.generics, &ty, bound);
+        }
+        derived
+    }
+
+    /// Returns an implementation of `lhs_ty op rhs_ty` whose method evaluates
+    /// `lhs_expr.op(rhs_expr)`, where `self` and `rhs` are the operands of the new method.
+    fn delegate(
+        &self,
+        lhs_ty: Type,
+        rhs_ty: Type,
+        lhs_expr: TokenStream,
+        rhs_expr: TokenStream,
+    ) -> Self {
+        let fn_ident = &self.item_fn.ident;
+
+        self.delegate_with(lhs_ty, rhs_ty, quote!(#lhs_expr.#fn_ident(#rhs_expr)))
+    }
+
+    /// Returns an implementation of `lhs_ty op rhs_ty` whose method evaluates `body`, where
+    /// `self` and `rhs` are the operands of the new method.
+    ///
+    /// The new method takes its operands as the method of `self` does, i.e., by value or by
+    /// reference, and returns the output type under its name in the trait.
+    ///
+    /// Operand references with elided lifetimes get fresh named lifetimes `'lhs` and `'rhs`, and
+    /// generic parameters which the new implementation no longer mentions are pruned.
+    fn delegate_with(&self, lhs_ty: Type, rhs_ty: Type, body: TokenStream) -> Self {
+        let mut generics = self.generics.clone();
+        let lhs_ty = name_lifetime(lhs_ty, &mut generics, "lhs");
+        let rhs_ty = name_lifetime(rhs_ty, &mut generics, "rhs");
+        let named = named_lifetimes(&self.generics, &generics);
+
+        let fn_ident = &self.item_fn.ident;
+        let out_ident = self.output_ident();
+        let receiver = match &self.item_fn.lhs_arg.reference {
+            Some((and_token, lifetime)) => {
+                let mutability = &self.item_fn.lhs_arg.mutability;
+                quote!(#and_token #lifetime #mutability self)
+            }
+            None => quote!(self),
+        };
+        let rhs_arg_ty = self.rhs_arg_ty(&rhs_ty);
         let item_fn = parse_quote! {
-            fn #fn_ident(self, rhs: #rhs_ty) -> Self::Output {
-                rhs.#fn_ident(self)
+            fn #fn_ident(#receiver, rhs: #rhs_arg_ty) -> Self::#out_ident {
+                #body
             }
         };
 
-        BinOpImpl {
+        let mut derived = BinOpImpl {
+            generics,
             lhs_ty,
             rhs_ty,
             item_fn,
             ..self.clone()
         }
+        .pruned();
+        let tokens = derived.to_token_stream();
+        allow_named_lifetimes(&mut derived.attrs, &named, &tokens);
+        derived
+    }
+
+    /// Removes the generic parameters which `self` no longer mentions outside of its generics,
+    /// along with the where-predicates on them; see [`prune_generics`].
+    fn pruned(mut self) -> Self {
+        let lhs_ty = &self.lhs_ty;
+        let rhs_ty = &self.rhs_ty;
+        let trait_args = &self.trait_args;
+        let item_out = &self.item_out;
+        let item_fn = &self.item_fn;
+        let items = &self.items;
+        let mentioned = quote!(#lhs_ty #rhs_ty #(#trait_args)* #item_out #item_fn #(#items)*);
+
+        prune_generics(&mut self.generics, &mentioned);
+        self
+    }
+
+    /// Checks whether the output type mentions a generic parameter which the operand types and
+    /// trait arguments leave unconstrained, as for `type Output = Thing<'a>` once `&'a A` is
+    /// dereferenced to `A`; rustc rejects such implementations.
+    fn output_unconstrained(&self) -> bool {
+        let lhs_ty = &self.lhs_ty;
+        let rhs_ty = &self.rhs_ty;
+        let trait_args = &self.trait_args;
+        let header = quote!(#lhs_ty #rhs_ty #(#trait_args)*);
+        let output = self.output_ty().to_token_stream();
+
+        self.generics.params.iter().any(|param| {
+            mentions_param(output.clone(), param) && !mentions_param(header.clone(), param)
+        })
+    }
+
+    /// Returns the type of the method argument `rhs` for the rhs type `rhs_ty`, which is
+    /// `&rhs_ty` (or `&mut rhs_ty`) if the method of `self` takes its rhs by reference.
+    fn rhs_arg_ty(&self, rhs_ty: &Type) -> Type {
+        match self.item_fn.rhs_arg_ty() {
+            Some(Type::Reference(ref_ty)) if tokens_eq(&ref_ty.elem, &self.rhs_ty) => {
+                Type::Reference(TypeReference {
+                    elem: Box::new(rhs_ty.clone()),
+                    ..ref_ty.clone()
+                })
+            }
+            _ => rhs_ty.clone(),
+        }
+    }
+
+    /// Checks whether `ty` is, up to references, a primitive type, an array, slice, or tuple, or
+    /// one of the type parameters, none of which are local to the crate implementing `self`.
+    fn foreign_operand(&self, ty: &Type) -> bool {
+        const PRIMITIVES: [&str; 17] = [
+            "bool", "char", "str", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize", "u8",
+            "u16", "u32", "u64", "u128", "usize",
+        ];
+
+        match placeholder(ty) {
+            Some(ident) => {
+                PRIMITIVES.iter().any(|primitive| ident == primitive)
+                    || self
+                        .generics
+                        .type_params()
+                        .any(|param| param.ident == *ident)
+            }
+            None => {
+                let mut ty = ty;
+                while let Type::Reference(ref_ty) = ty {
+                    ty = &ref_ty.elem;
+                }
+                matches!(ty, Type::Array(_) | Type::Slice(_) | Type::Tuple(_))
+            }
+        }
+    }
+
+    /// Checks whether the method takes both operands by value, as do those of `std::ops`.
+    pub fn operands_by_value(&self) -> bool {
+        self.item_fn.lhs_arg.reference.is_none()
+            && tokens_eq(&self.rhs_arg_ty(&self.rhs_ty), &self.rhs_ty)
+    }
+
+    /// Returns the name of the trait, e.g. `Add` for `std::ops::Add`.
+    pub fn op_ident(&self) -> Option<&Ident> {
+        self.trait_.segments.last().map(|segment| &segment.ident)
+    }
+
+    /// Checks whether the trait is one of the binary operations of `std::ops`.
+    pub fn is_std_op(&self) -> bool {
+        catalog::lookup(&self.trait_).is_some_and(|info| info.kind == OpKind::Binary)
+    }
+
+    /// Returns `type Output = C;`, inferred from the method if it is missing.
+    pub fn output(&self) -> BinOpOutput {
+        match &self.item_out {
+            Some(item_out) => item_out.clone(),
+            None => {
+                let out_ty = &self.item_fn.out_ty;
+                parse_quote!(type Output = #out_ty;)
+            }
+        }
+    }
+
+    /// Returns the output type `C`, inferred from the method if `type Output` is missing.
+    pub fn output_ty(&self) -> &Type {
+        match &self.item_out {
+            Some(item_out) => &item_out.ty,
+            None => &self.item_fn.out_ty,
+        }
+    }
+
+    /// Returns the name of the output type, i.e., `Output` unless a custom trait names it
+    /// otherwise.
+    pub fn output_ident(&self) -> Ident {
+        match &self.item_out {
+            Some(item_out) => item_out.ident.clone(),
+            None => format_ident!("Output"),
+        }
+    }
+
+    /// Checks whether the output type is the lhs type.
+    pub fn outputs_lhs(&self) -> bool {
+        let output_ty = self.output_ty();
+        tokens_eq(output_ty, &self.lhs_ty) || tokens_eq(output_ty, &quote!(Self))
+    }
+
+    /// Returns the type parameter `T` if `self` implements a `std::ops` trait for `T` or `&T`,
+    /// which the orphan rule forbids since the trait is foreign and `T` is not covered.
+    pub fn uncovered_param(&self) -> Option<&Ident> {
+        if !self.is_std_op() {
+            return None;
+        }
+
+        let param = placeholder(&self.lhs_ty)?;
+        self.generics
+            .type_params()
+            .any(|type_param| type_param.ident == *param)
+            .then_some(param)
+    }
+
+    /// Returns the type `<A as Op<B>>::Output`, i.e., the output type of `self` as seen from
+    /// other implementations.
+    pub fn output_projection(&self) -> Type {
+        let lhs_ty = &self.lhs_ty;
+        let rhs_ty = &self.rhs_ty;
+        let trait_ = &self.trait_;
+        let out_ident = self.output_ident();
+
+        let trait_args = &self.trait_args;
+
+        parse_quote!(<#lhs_ty as #trait_<#rhs_ty #(, #trait_args)*>>::#out_ident)
+    }
+
+    /// Returns `self` declaring its output type as the projection onto the output of `base`,
+    /// so that the output type of an implementation derived from `base` is defined in exactly
+    /// one place.
+    ///
+    /// The output type is left as is if `self` is `base` itself, or if `base` involves
+    /// references or lifetimes, as these cannot be named in the output type of `self`.
+    ///
+    /// For example, the reference form derived from `A + B` projects onto its output, while the
+    /// owned form derived from `&A + B` keeps the output type of the base:
+    /// ```
+    /// # use syn::{parse_quote, Type};
+    /// # use xops_core::BinOpImpl;
+    /// let base: BinOpImpl = parse_quote! {
+    ///     impl Add<B> for A {
+    ///         type Output = C;
+    ///         fn add(self, rhs: B) -> C { C::new(self, rhs) }
+    ///     }
+    /// };
+    /// let derived = base.ref_lhs_clone().with_output_of(&base);
+    /// let projection: Type = parse_quote!(<A as Add<B>>::Output);
+    /// assert_eq!(derived.output_ty(), &projection);
+    ///
+    /// let ref_base: BinOpImpl = parse_quote! {
+    ///     impl Add<B> for &A {
+    ///         type Output = C;
+    ///         fn add(self, rhs: B) -> C { C::new(self, rhs) }
+    ///     }
+    /// };
+    /// let derived = ref_base.try_deref_lhs().unwrap().with_output_of(&ref_base);
+    /// let output: Type = parse_quote!(C);
+    /// assert_eq!(derived.output_ty(), &output);
+    /// ```
+    pub fn with_output_of(mut self, base: &BinOpImpl) -> Self {
+        let is_base = tokens_eq(&self.trait_, &base.trait_)
+            && tokens_eq(&self.lhs_ty, &base.lhs_ty)
+            && tokens_eq(&self.rhs_ty, &base.rhs_ty);
+
+        if !is_base && !mentions_borrows(&base.lhs_ty) && !mentions_borrows(&base.rhs_ty) {
+            self.item_out = Some(BinOpOutput {
+                ty: base.output_projection(),
+                ..self.output()
+            });
+        }
+        self
+    }
+
+    /// Renames the trait and method of `self`, leaving the method body untouched.
+    fn with_op(mut self, trait_: Path, fn_ident: Ident) -> Self {
+        self.trait_ = trait_;
+        self.item_fn.ident = fn_ident;
+        self
+    }
+
+    /// Adds `ty: Copy` to the where clause if `ty` involves any of the generic parameters.
+    fn with_copy_bound(mut self, ty: &Type) -> Self {
+        add_bound(&mut self.generics, ty, quote!(::core::marker::Copy));
+        self
+    }
+
+    /// Adds `ty: Clone` to the where clause if `ty` involves any of the generic parameters.
+    fn with_clone_bound(mut self, ty: &Type) -> Self {
+        add_bound(&mut self.generics, ty, quote!(::core::clone::Clone));
+        self
+    }
+}
+
+/// How a derived implementation recovers an operand of the base implementation from its own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Recover {
+    /// the operand types agree, so the operand is passed along as is
+    Move,
+    /// the base takes `&T` and the derived implementation takes `T`
+    Borrow,
+    /// the base takes `&mut T` and the derived implementation takes `T`, bound with `mut`
+    BorrowMut,
+    /// the base takes `&T` and the derived implementation takes `&mut T` or a smart pointer to
+    /// `T`, which is reborrowed
+    Reborrow,
+    /// the base takes `T` and the derived implementation takes `&T`, which is cloned
+    Clone,
+    /// the base takes `T` and the derived implementation takes `&T`, which is dereferenced
+    Copy,
+    /// the base takes `T` and the derived implementation takes a smart pointer to `T`, out of
+    /// which `T` is moved or cloned
+    Unwrap(SmartPointer),
+}
+
+impl Recover {
+    /// Returns the operand type of a derived implementation with the requested ownership,
+    /// along with how to recover the base operand of type `ty` from it, where `owned` is
+    /// either `Clone` or `Copy`.
+    fn reshape(ty: &Type, as_ref: bool, owned: Recover) -> (Type, Self) {
+        match (Recover::unborrow(ty), as_ref) {
+            (Some(_), true) | (None, false) => (ty.clone(), Recover::Move),
+            (Some(unborrowed), false) => unborrowed,
+            (None, true) => (ty.as_ref(), owned),
+        }
+    }
+
+    /// If `ty` is `&T` or `&mut T`, returns `T` along with how to recover `ty` from it.
+    fn unborrow(ty: &Type) -> Option<(Type, Self)> {
+        match ty {
+            Type::Reference(ref_ty) if ref_ty.mutability.is_some() => {
+                Some(((*ref_ty.elem).clone(), Recover::BorrowMut))
+            }
+            Type::Reference(ref_ty) => Some(((*ref_ty.elem).clone(), Recover::Borrow)),
+            _ => None,
+        }
+    }
+
+    /// recovers the base operand `expr` in method receiver position
+    fn receiver(self, expr: TokenStream) -> TokenStream {
+        match self {
+            Recover::Borrow => quote!((&#expr)),
+            Recover::BorrowMut => quote!((&mut #expr)),
+            Recover::Reborrow => quote!((&*#expr)),
+            Recover::Copy | Recover::Unwrap(SmartPointer::Box) => quote!((*#expr)),
+            _ => self.argument(expr),
+        }
+    }
+
+    /// recovers the base operand `expr` in method argument position
+    fn argument(self, expr: TokenStream) -> TokenStream {
+        match self {
+            Recover::Move => expr,
+            Recover::Borrow => quote!(&#expr),
+            Recover::BorrowMut => quote!(&mut #expr),
+            Recover::Reborrow => quote!(&*#expr),
+            Recover::Clone => quote!(#expr.clone()),
+            Recover::Copy => quote!(*#expr),
+            Recover::Unwrap(pointer) => pointer.unwrap(expr),
+        }
+    }
+
+    /// whether recovering the base operand may clone it
+    fn clones(self) -> bool {
+        match self {
+            Recover::Clone => true,
+            Recover::Unwrap(pointer) => pointer != SmartPointer::Box,
+            _ => false,
+        }
+    }
+}
+
+impl SmartPointer {
+    /// Returns the pointer type to `ty`, e.g., `Rc<T>`.
+    pub fn wrap(self, ty: &Type) -> Type {
+        match self {
+            SmartPointer::Box => parse_quote!(::std::boxed::Box<#ty>),
+            SmartPointer::Rc => parse_quote!(::std::rc::Rc<#ty>),
+            SmartPointer::Arc => parse_quote!(::std::sync::Arc<#ty>),
+            SmartPointer::Cow => parse_quote!(::std::borrow::Cow<'_, #ty>),
+        }
+    }
+
+    /// Returns an expression moving the pointee out of the pointer `expr`, which clones it if it
+    /// is shared or borrowed.
+    pub fn unwrap(self, expr: TokenStream) -> TokenStream {
+        match self {
+            SmartPointer::Box => quote!(*#expr),
+            SmartPointer::Rc => {
+                quote!(::std::rc::Rc::try_unwrap(#expr).unwrap_or_else(|rc| (*rc).clone()))
+            }
+            SmartPointer::Arc => {
+                quote!(::std::sync::Arc::try_unwrap(#expr).unwrap_or_else(|arc| (*arc).clone()))
+            }
+            SmartPointer::Cow => quote!(::std::borrow::Cow::into_owned(#expr)),
+        }
+    }
+
+    /// Checks whether the pointer is `#[fundamental]`, i.e., local whenever its pointee is,
+    /// which only `Box` is.
+    pub fn is_fundamental(self) -> bool {
+        self == SmartPointer::Box
+    }
+}
+
+impl BinOpFn {
+    /// Returns the type of the argument `rhs`.
+    pub fn rhs_arg_ty(&self) -> Option<&Type> {
+        match &self.rhs_arg {
+            FnArg::Typed(pat_type) => Some(&pat_type.ty),
+            FnArg::Receiver(_) => None,
+        }
     }
 }
 
@@ -267,6 +1344,7 @@ impl BinOpImpl {
 
 impl ToTokens for BinOpOutput {
     fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.append_all(&self.attrs);
         self.type_token.to_tokens(tokens);
         self.ident.to_tokens(tokens);
         self.eq_token.to_tokens(tokens);
@@ -287,30 +1365,35 @@ impl ToTokens for BinOpFn {
         });
         self.arrow_token.to_tokens(tokens);
         self.out_ty.to_tokens(tokens);
+        self.where_clause.to_tokens(tokens);
         self.block.to_tokens(tokens);
     }
 }
 
 macro_rules! lr_angled {
-    ($item:expr) => {{
+    ($item:expr, $args:expr) => {{
         let rhs_ty = $item;
-        quote!(<#rhs_ty>)
+        let trait_args = $args;
+        quote!(<#rhs_ty #(, #trait_args)*>)
     }};
 }
 
 impl ToTokens for BinOpImpl {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         tokens.append_all(&self.attrs);
+        self.defaultness.to_tokens(tokens);
+        self.unsafety.to_tokens(tokens);
         self.impl_token.to_tokens(tokens);
         self.generics.to_tokens(tokens);
         self.trait_.to_tokens(tokens);
-        tokens.append_all(lr_angled!(&self.rhs_ty));
+        tokens.append_all(lr_angled!(&self.rhs_ty, &self.trait_args));
         self.for_token.to_tokens(tokens);
         self.lhs_ty.to_tokens(tokens);
         self.generics.where_clause.to_tokens(tokens);
         self.brace_token.surround(tokens, |tokens| {
             self.item_out.to_tokens(tokens);
             self.item_fn.to_tokens(tokens);
+            tokens.append_all(&self.items);
         });
     }
 }
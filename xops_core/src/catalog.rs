@@ -0,0 +1,166 @@
+//! Metadata on the operator traits of the standard library.
+//!
+//! A trait path is looked up by its last segment, provided the segments before it name the
+//! trait's module, as in `Add`, `ops::Add`, `std::ops::Add`, or `::core::ops::Add`. Any other path
+//! is taken to be a custom trait, which is handled generically.
+
+use syn::Path;
+
+/// The kind of operation a trait overloads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OpKind {
+    /// `a op b`, e.g., `Add`
+    Binary,
+    /// `op a`, e.g., `Neg`
+    Unary,
+    /// `a op b` returning a `bool` or an ordering, e.g., `PartialEq`
+    Comparison,
+    /// `a[b]`
+    Index,
+}
+
+/// Metadata on an operator trait of the standard library.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OpInfo {
+    /// the trait, e.g., `Add`
+    pub trait_name: &'static str,
+    /// the module of `core` which defines the trait, e.g., `ops`
+    pub module: &'static str,
+    /// the operator method, e.g., `add`
+    pub method: &'static str,
+    /// the compound assignment trait and method, e.g., `AddAssign` and `add_assign`
+    pub assign: Option<(&'static str, &'static str)>,
+    /// the operator symbol, e.g., `+`
+    pub symbol: &'static str,
+    pub kind: OpKind,
+    /// whether `a op b == b op a` is expected, as for `+` but not for `-`
+    pub commutative: bool,
+}
+
+impl OpInfo {
+    const fn binary(
+        trait_name: &'static str,
+        method: &'static str,
+        assign: (&'static str, &'static str),
+        symbol: &'static str,
+        commutative: bool,
+    ) -> Self {
+        OpInfo {
+            trait_name,
+            module: "ops",
+            method,
+            assign: Some(assign),
+            symbol,
+            kind: OpKind::Binary,
+            commutative,
+        }
+    }
+
+    const fn other(
+        trait_name: &'static str,
+        module: &'static str,
+        method: &'static str,
+        symbol: &'static str,
+        kind: OpKind,
+        commutative: bool,
+    ) -> Self {
+        OpInfo {
+            trait_name,
+            module,
+            method,
+            assign: None,
+            symbol,
+            kind,
+            commutative,
+        }
+    }
+
+    /// the trait's path in `core`, e.g., `::core::ops::Add`
+    pub fn path(&self) -> Path {
+        syn::parse_str(&format!("::core::{}::{}", self.module, self.trait_name))
+            .expect("catalog paths are valid")
+    }
+
+    /// the compound assignment trait's path in `core`, e.g., `::core::ops::AddAssign`
+    pub fn assign_path(&self) -> Option<Path> {
+        let (assign_trait, _) = self.assign?;
+        syn::parse_str(&format!("::core::{}::{}", self.module, assign_trait)).ok()
+    }
+}
+
+/// All operator traits known to xops.
+pub const OPS: [OpInfo; 15] = [
+    OpInfo::binary("Add", "add", ("AddAssign", "add_assign"), "+", true),
+    OpInfo::binary("Sub", "sub", ("SubAssign", "sub_assign"), "-", false),
+    OpInfo::binary("Mul", "mul", ("MulAssign", "mul_assign"), "*", true),
+    OpInfo::binary("Div", "div", ("DivAssign", "div_assign"), "/", false),
+    OpInfo::binary("Rem", "rem", ("RemAssign", "rem_assign"), "%", false),
+    OpInfo::binary(
+        "BitAnd",
+        "bitand",
+        ("BitAndAssign", "bitand_assign"),
+        "&",
+        true,
+    ),
+    OpInfo::binary("BitOr", "bitor", ("BitOrAssign", "bitor_assign"), "|", true),
+    OpInfo::binary(
+        "BitXor",
+        "bitxor",
+        ("BitXorAssign", "bitxor_assign"),
+        "^",
+        true,
+    ),
+    OpInfo::binary("Shl", "shl", ("ShlAssign", "shl_assign"), "<<", false),
+    OpInfo::binary("Shr", "shr", ("ShrAssign", "shr_assign"), ">>", false),
+    OpInfo::other("Neg", "ops", "neg", "-", OpKind::Unary, false),
+    OpInfo::other("Not", "ops", "not", "!", OpKind::Unary, false),
+    OpInfo::other("PartialEq", "cmp", "eq", "==", OpKind::Comparison, true),
+    OpInfo::other(
+        "PartialOrd",
+        "cmp",
+        "partial_cmp",
+        "<",
+        OpKind::Comparison,
+        false,
+    ),
+    OpInfo::other("Index", "ops", "index", "[]", OpKind::Index, false),
+];
+
+/// Returns the operator trait which `path` names, if it is one of [`OPS`].
+pub fn lookup(path: &Path) -> Option<&'static OpInfo> {
+    let ident = &path.segments.last()?.ident;
+    let info = OPS.iter().find(|info| ident == info.trait_name)?;
+
+    in_module(path, info.module).then_some(info)
+}
+
+/// Returns the operator trait whose compound assignment trait `path` names, e.g., the entry of
+/// `Add` for `AddAssign`.
+pub fn lookup_assign(path: &Path) -> Option<&'static OpInfo> {
+    let ident = &path.segments.last()?.ident;
+    let info = OPS.iter().find(|info| {
+        info.assign
+            .is_some_and(|(assign_trait, _)| ident == assign_trait)
+    })?;
+
+    in_module(path, info.module).then_some(info)
+}
+
+/// Checks whether the segments of `path` before the trait name are a suffix of
+/// `core::module`/`std::module`.
+fn in_module(path: &Path, module: &str) -> bool {
+    let prefix: Vec<String> = path
+        .segments
+        .iter()
+        .rev()
+        .skip(1)
+        .map(|segment| segment.ident.to_string())
+        .collect();
+
+    match &prefix[..] {
+        [] => path.leading_colon.is_none(),
+        [m] => m == module && path.leading_colon.is_none(),
+        [m, krate] => m == module && (krate == "core" || krate == "std"),
+        _ => false,
+    }
+}
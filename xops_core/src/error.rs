@@ -0,0 +1,65 @@
+use std::fmt::Display;
+
+use proc_macro2::{Span, TokenStream};
+use quote::ToTokens;
+
+/// An error pointing at the tokens responsible for it, e.g., a macro argument which does not
+/// apply to the given implementation.
+///
+/// Several errors combine into one, so that every problem is reported at once.
+#[derive(Clone, Debug)]
+pub struct Error(syn::Error);
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub fn new<T: Display>(span: Span, message: T) -> Self {
+        Error(syn::Error::new(span, message))
+    }
+
+    pub fn new_spanned<T: ToTokens, U: Display>(tokens: T, message: U) -> Self {
+        Error(syn::Error::new_spanned(tokens, message))
+    }
+
+    /// adds `other` to the errors reported by `self`
+    pub fn combine(&mut self, other: Error) {
+        self.0.combine(other.0)
+    }
+
+    /// combines all of `errors`, or returns `Ok` if there are none
+    pub fn combine_all<I: IntoIterator<Item = Error>>(errors: I) -> Result<()> {
+        let mut errors = errors.into_iter();
+        match errors.next() {
+            Some(mut combined) => {
+                errors.for_each(|error| combined.combine(error));
+                Err(combined)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// one `compile_error!` invocation per error, each spanned at its tokens
+    pub fn to_compile_error(&self) -> TokenStream {
+        self.0.to_compile_error()
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<syn::Error> for Error {
+    fn from(error: syn::Error) -> Self {
+        Error(error)
+    }
+}
+
+impl From<Error> for syn::Error {
+    fn from(error: Error) -> Self {
+        error.0
+    }
+}
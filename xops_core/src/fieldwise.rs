@@ -0,0 +1,64 @@
+use proc_macro2::TokenStream;
+use syn::parse_quote;
+
+mod parse;
+pub use parse::*;
+
+mod write;
+
+use crate::{catalog::OpKind, utils::print_tokens, Error, OpsArgs, Result};
+
+impl Fieldwise {
+    pub fn expand(&self, args: OpsArgs) -> TokenStream {
+        if args.binop.dev_print {
+            dbg!(&args);
+            print_tokens("fieldwise struct", self.ty());
+        }
+
+        if let Err(err) = check(&args, self) {
+            return err.to_compile_error();
+        }
+
+        let binop_args = args.binop_args();
+        let unop_args = args.unop_args();
+
+        args.ops
+            .iter()
+            .map(|op| match op.info.kind {
+                OpKind::Unary => self.unop_impl(op.info).expand(unop_args.clone()),
+                _ => self.binop_impl(op.info).expand(binop_args.clone()),
+            })
+            .collect()
+    }
+}
+
+/// Checks that operators are given, and that binary ones are given without rhs types other
+/// than `Self`.
+fn check(args: &OpsArgs, fieldwise: &Fieldwise) -> Result<()> {
+    if args.ops.is_empty() {
+        return Err(Error::new_spanned(
+            &fieldwise.ident,
+            "`FieldwiseOps` needs the operators to apply, as in `#[fieldwise(Add, Sub)]`",
+        ));
+    }
+
+    let errors = args
+        .ops
+        .iter()
+        .filter(|op| {
+            op.rhs_types
+                .iter()
+                .any(|rhs_ty| rhs_ty != &parse_quote!(Self))
+        })
+        .map(|op| {
+            Error::new_spanned(
+                &op.path,
+                format!(
+                    "`FieldwiseOps` applies `{}` between two values of `{}`, so it takes no rhs \
+                     types",
+                    op.info.trait_name, fieldwise.ident,
+                ),
+            )
+        });
+    Error::combine_all(errors)
+}
@@ -0,0 +1,112 @@
+use std::convert::TryFrom;
+
+use syn::{
+    parse::{Parse, ParseStream},
+    Attribute, Data, DeriveInput, Fields, Generics, Ident, Member, Meta, NestedMeta, Type,
+};
+
+// structs -----------------------------------------------------------------------------------------
+
+/// A field of a struct deriving `FieldwiseOps`.
+///
+/// A field marked `#[fieldwise(skip)]` is left out of the operations, and the result takes it
+/// from the lhs instead.
+#[derive(Clone, Debug)]
+pub struct FieldwiseField {
+    pub member: Member,
+    pub ty: Type,
+    pub skip: bool,
+}
+
+/// A struct whose operators apply to each of its fields, as derived by `#[derive(FieldwiseOps)]`.
+///
+/// Example:
+/// ```ignore
+/// struct Rgb {
+///     r: f32,
+///     g: f32,
+///     b: f32,
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Fieldwise {
+    pub attrs: Vec<Attribute>,
+    pub ident: Ident,
+    pub generics: Generics,
+    pub fields: Vec<FieldwiseField>,
+    /// whether the struct is a unit struct, as in `struct Origin;`
+    pub unit: bool,
+}
+
+// parsing -----------------------------------------------------------------------------------------
+
+impl Parse for Fieldwise {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Fieldwise::try_from(input.parse::<DeriveInput>()?)
+    }
+}
+
+impl TryFrom<DeriveInput> for Fieldwise {
+    type Error = syn::Error;
+
+    fn try_from(input: DeriveInput) -> syn::Result<Self> {
+        let fields = match input.data {
+            Data::Struct(data) => data.fields,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "`FieldwiseOps` can only be derived for structs",
+                ))
+            }
+        };
+
+        let unit = matches!(fields, Fields::Unit);
+        let fields = fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| {
+                let member = match &field.ident {
+                    Some(ident) => Member::Named(ident.clone()),
+                    None => Member::Unnamed(index.into()),
+                };
+                Ok(FieldwiseField {
+                    member,
+                    ty: field.ty.clone(),
+                    skip: parse_skip(&field.attrs)?,
+                })
+            })
+            .collect::<syn::Result<_>>()?;
+
+        Ok(Fieldwise {
+            attrs: input.attrs,
+            ident: input.ident,
+            generics: input.generics,
+            fields,
+            unit,
+        })
+    }
+}
+
+/// Checks whether the attributes `#[fieldwise(...)]` of a field say `skip`, the only argument
+/// they take.
+fn parse_skip(attrs: &[Attribute]) -> syn::Result<bool> {
+    let mut skip = false;
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("fieldwise")) {
+        let items = match attr.parse_meta()? {
+            Meta::List(list) => list.nested,
+            meta => {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    "expected `#[fieldwise(skip)]`",
+                ))
+            }
+        };
+        for item in items {
+            match item {
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip") => skip = true,
+                item => return Err(syn::Error::new_spanned(item, "expected `skip`")),
+            }
+        }
+    }
+    Ok(skip)
+}
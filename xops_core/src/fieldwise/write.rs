@@ -0,0 +1,164 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_quote, Generics, Member, Type};
+
+use crate::{
+    catalog::OpInfo,
+    utils::{add_bound, tokens_eq},
+    BinOpImpl, Fieldwise, FieldwiseField, UnOpImpl,
+};
+
+impl Fieldwise {
+    /// Returns the struct applied to its own parameters, e.g., `V<T>`.
+    pub fn ty(&self) -> Type {
+        let ident = &self.ident;
+        let (_, ty_generics, _) = self.generics.split_for_impl();
+
+        parse_quote!(#ident #ty_generics)
+    }
+
+    /// Returns an expression constructing the struct from `values`, one for each field in order.
+    pub fn construct(&self, values: &[TokenStream]) -> TokenStream {
+        let ident = &self.ident;
+        let members = self.fields.iter().map(|field| &field.member);
+
+        let tuple = self
+            .fields
+            .first()
+            .is_some_and(|field| matches!(field.member, Member::Unnamed(_)));
+
+        if self.unit {
+            quote!(#ident)
+        } else if tuple {
+            quote!(#ident(#(#values),*))
+        } else {
+            quote!(#ident { #(#members: #values),* })
+        }
+    }
+
+    /// Returns the parameters of the struct with each type of a field taking part in `op` bound
+    /// by it, as in `T: Op<Output = T>`, if the type involves generic parameters.
+    fn generics_bound_by(&self, op: &OpInfo) -> Generics {
+        let trait_ = op.path();
+        let mut generics = self.generics.clone();
+        let mut bound: Vec<&Type> = Vec::new();
+        for field in self.fields.iter().filter(|field| !field.skip) {
+            let ty = &field.ty;
+            if !bound.iter().any(|bound_ty| tokens_eq(bound_ty, ty)) {
+                add_bound(&mut generics, ty, quote!(#trait_<Output = #ty>));
+                bound.push(ty);
+            }
+        }
+        generics
+    }
+
+    /// Returns an implementation of the binary operation `op` between two values of the struct,
+    /// which applies `op` to each pair of matching fields; fields marked `skip` are taken from
+    /// the lhs.
+    ///
+    /// The type of each field is bound by `op` if it involves generic parameters.
+    ///
+    /// In other words, for `struct V<T> { x: T, y: T }`, this produces the implementation
+    /// ```ignore
+    /// impl<T> Op<V<T>> for V<T>
+    /// where
+    ///     T: Op<Output = T>,
+    /// {
+    ///     type Output = V<T>;
+    ///
+    ///     fn op(self, rhs: V<T>) -> V<T> {
+    ///         V {
+    ///             x: Op::op(self.x, rhs.x),
+    ///             y: Op::op(self.y, rhs.y),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn binop_impl(&self, op: &OpInfo) -> BinOpImpl {
+        let ty = self.ty();
+        let trait_ = op.path();
+        let fn_ident = format_ident!("{}", op.method);
+        let generics = self.generics_bound_by(op);
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+        let values: Vec<TokenStream> = self
+            .fields
+            .iter()
+            .map(|FieldwiseField { member, skip, .. }| {
+                if *skip {
+                    quote!(self.#member)
+                } else {
+                    quote!(#trait_::#fn_ident(self.#member, rhs.#member))
+                }
+            })
+            .collect();
+        let body = self.construct(&values);
+        let rhs = if self.fields.iter().all(|field| field.skip) {
+            format_ident!("_rhs")
+        } else {
+            format_ident!("rhs")
+        };
+
+        parse_quote! {
+            impl #impl_generics #trait_<#ty> for #ty #where_clause {
+                type Output = #ty;
+
+                fn #fn_ident(self, #rhs: #ty) -> #ty {
+                    #body
+                }
+            }
+        }
+    }
+
+    /// Returns an implementation of the unary operation `op` on the struct, which applies `op`
+    /// to each field; fields marked `skip` are kept as they are.
+    ///
+    /// The type of each field is bound by `op` if it involves generic parameters.
+    ///
+    /// In other words, for `struct V<T> { x: T, y: T }`, this produces the implementation
+    /// ```ignore
+    /// impl<T> Op for V<T>
+    /// where
+    ///     T: Op<Output = T>,
+    /// {
+    ///     type Output = V<T>;
+    ///
+    ///     fn op(self) -> V<T> {
+    ///         V {
+    ///             x: Op::op(self.x),
+    ///             y: Op::op(self.y),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn unop_impl(&self, op: &OpInfo) -> UnOpImpl {
+        let ty = self.ty();
+        let trait_ = op.path();
+        let fn_ident = format_ident!("{}", op.method);
+        let generics = self.generics_bound_by(op);
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+        let values: Vec<TokenStream> = self
+            .fields
+            .iter()
+            .map(|FieldwiseField { member, skip, .. }| {
+                if *skip {
+                    quote!(self.#member)
+                } else {
+                    quote!(#trait_::#fn_ident(self.#member))
+                }
+            })
+            .collect();
+        let body = self.construct(&values);
+
+        parse_quote! {
+            impl #impl_generics #trait_ for #ty #where_clause {
+                type Output = #ty;
+
+                fn #fn_ident(self) -> #ty {
+                    #body
+                }
+            }
+        }
+    }
+}
@@ -1,7 +1,35 @@
 /// Handling Binary Operations
 mod binop;
 pub use crate::binop::{
-    read_impl as binop_read, BinOpArgs, BinOpFn, BinOpImpl, BinOpOutput,
+    read_impl as binop_read, AssignStrategy, BinOpArgs, BinOpFn, BinOpImpl, BinOpOutput,
+    CommuteMode, DeriveDivArgs, ProductArgs, SmartPointer, SumArgs, TypeList,
 };
 
+/// Handling Compound Assignment Operations
+mod assignop;
+pub use crate::assignop::{AssignOpArgs, AssignOpFn, AssignOpImpl};
+
+/// Handling Unary Operations
+mod unop;
+pub use crate::unop::{UnOpArgs, UnOpFn, UnOpImpl};
+
+/// Defining Operator Traits
+mod optrait;
+pub use crate::optrait::{OpTrait, OpTraitArgs};
+
+/// Forwarding Operators of Newtypes
+mod newtype;
+pub use crate::newtype::{ForwardedOp, Newtype, OpsArgs};
+
+/// Applying Operators Field by Field
+mod fieldwise;
+pub use crate::fieldwise::{Fieldwise, FieldwiseField};
+
+/// Describing the Operators of the Standard Library
+pub mod catalog;
+
+/// Reporting Errors
+mod error;
+pub use crate::error::{Error, Result};
+
 mod utils;
@@ -0,0 +1,70 @@
+use darling::util::SpannedValue;
+use proc_macro2::{Span, TokenStream};
+
+mod parse;
+pub use parse::*;
+
+mod write;
+
+use crate::{catalog::OpKind, utils::print_tokens, BinOpArgs, Error, UnOpArgs};
+
+impl Newtype {
+    pub fn expand(&self, args: OpsArgs) -> TokenStream {
+        if args.binop.dev_print {
+            dbg!(&args);
+            print_tokens("ops newtype", self.ty());
+        }
+
+        if args.ops.is_empty() {
+            return Error::new_spanned(
+                &self.ident,
+                "`Ops` needs the operators to forward, as in `#[ops(Add, Mul(f64))]`",
+            )
+            .to_compile_error();
+        }
+
+        let binop_args = args.binop_args();
+        let unop_args = args.unop_args();
+
+        args.ops
+            .iter()
+            .flat_map(|op| match op.info.kind {
+                OpKind::Unary => vec![self.unop_impl(op.info).expand(unop_args.clone())],
+                _ => op
+                    .rhs_types
+                    .iter()
+                    .map(|rhs_ty| self.binop_impl(op.info, rhs_ty).expand(binop_args.clone()))
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+impl OpsArgs {
+    /// Returns the arguments with which each forwarded binary operation is expanded, where
+    /// `refs` stands for `all_refs`.
+    pub fn binop_args(&self) -> BinOpArgs {
+        if self.refs {
+            BinOpArgs {
+                all_refs: SpannedValue::new(true, Span::call_site()),
+                ..self.binop.clone()
+            }
+        } else {
+            self.binop.clone()
+        }
+    }
+
+    /// Returns the arguments with which each forwarded unary operation is expanded, where `refs`
+    /// stands for `refs_clone`, or for `refs_copy` if that is given as well.
+    pub fn unop_args(&self) -> UnOpArgs {
+        UnOpArgs {
+            dev_print: self.binop.dev_print,
+            refs_clone: SpannedValue::new(
+                (self.refs || *self.binop.refs_clone) && !*self.binop.refs_copy,
+                self.binop.refs_clone.span(),
+            ),
+            refs_copy: self.binop.refs_copy,
+            derefs: SpannedValue::default(),
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use std::convert::TryFrom;
+
+use darling::FromMeta;
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_quote, Attribute, Data, DeriveInput, Generics, Ident, Member, Meta, NestedMeta, Path,
+    Type,
+};
+
+use crate::{
+    catalog::{self, OpInfo, OpKind},
+    utils::{attr_items, pretty},
+    BinOpArgs, TypeList,
+};
+
+// structs -----------------------------------------------------------------------------------------
+
+/// Arguments for the derive macros `Ops` and `FieldwiseOps`, given by the attributes
+/// `#[ops(...)]` and `#[fieldwise(...)]` on the struct.
+///
+/// The operators to forward are listed by their traits, as in `Add` or `Mul(f64, i32)`; see
+/// [`ForwardedOp`]. The word `refs` derives every ownership form of each operation, and every
+/// other argument is one of [`BinOpArgs`], which applies to each forwarded binary operation.
+///
+/// Example:
+/// ```ignore
+/// #[ops(Add, Sub, Mul(f64), Neg, assign, refs)]
+/// ```
+#[derive(Clone, Default, Debug)]
+pub struct OpsArgs {
+    pub ops: Vec<ForwardedOp>,
+    pub refs: bool,
+    pub binop: BinOpArgs,
+}
+
+/// An operator trait of `std::ops` forwarded by `#[derive(Ops)]`, along with its rhs types if it
+/// is binary, e.g., `Mul(f64)`.
+///
+/// The rhs type `Self` stands for the newtype itself, and is the default for a binary trait
+/// given without rhs types.
+#[derive(Clone, Debug)]
+pub struct ForwardedOp {
+    pub path: Path,
+    pub info: &'static OpInfo,
+    pub rhs_types: Vec<Type>,
+}
+
+/// A struct with a single field, to which `#[derive(Ops)]` forwards the operators of the struct.
+///
+/// Example:
+/// ```ignore
+/// struct Meters(f64);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Newtype {
+    pub attrs: Vec<Attribute>,
+    pub ident: Ident,
+    pub generics: Generics,
+    pub member: Member,
+    pub field_ty: Type,
+}
+
+// parsing -----------------------------------------------------------------------------------------
+
+impl OpsArgs {
+    /// Parses the arguments of all the attributes `#[name(...)]` among `attrs` as one list.
+    pub fn from_attrs(attrs: &[Attribute], name: &str) -> darling::Result<Self> {
+        OpsArgs::from_list(&attr_items(attrs, name)?)
+    }
+}
+
+impl FromMeta for OpsArgs {
+    fn from_list(items: &[NestedMeta]) -> darling::Result<Self> {
+        let mut args = OpsArgs::default();
+        let mut binop_items = Vec::new();
+        let mut errors = Vec::new();
+        for item in items {
+            match item {
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("refs") => args.refs = true,
+                NestedMeta::Meta(meta) if names_trait(meta.path()) => {
+                    match ForwardedOp::from_meta(meta) {
+                        Ok(op) => args.ops.push(op),
+                        Err(e) => errors.push(e),
+                    }
+                }
+                item => binop_items.push(item.clone()),
+            }
+        }
+        match BinOpArgs::from_list(&binop_items) {
+            Ok(binop) => args.binop = binop,
+            Err(e) => errors.push(e),
+        }
+
+        if errors.is_empty() {
+            Ok(args)
+        } else {
+            Err(darling::Error::multiple(errors))
+        }
+    }
+}
+
+/// Checks whether `path` names a trait rather than an argument, i.e., whether it is capitalized.
+fn names_trait(path: &Path) -> bool {
+    path.segments
+        .last()
+        .and_then(|segment| segment.ident.to_string().chars().next())
+        .is_some_and(char::is_uppercase)
+}
+
+impl FromMeta for ForwardedOp {
+    fn from_meta(item: &Meta) -> darling::Result<Self> {
+        let path = item.path();
+        let info = catalog::lookup(path)
+            .filter(|info| matches!(info.kind, OpKind::Binary | OpKind::Unary))
+            .ok_or_else(|| {
+                darling::Error::custom(format!(
+                    "expected an arithmetic or bitwise operator of `std::ops`, such as `Add` or \
+                     `Neg`; found `{}`",
+                    pretty(path),
+                ))
+                .with_span(path)
+            })?;
+
+        let rhs_types = match (item, info.kind) {
+            (Meta::Path(_), OpKind::Binary) => vec![parse_quote!(Self)],
+            (Meta::Path(_), _) => Vec::new(),
+            (Meta::List(list), OpKind::Binary) if !list.nested.is_empty() => {
+                let items: Vec<NestedMeta> = list.nested.iter().cloned().collect();
+                TypeList::from_list(&items)?.0
+            }
+            (Meta::List(list), OpKind::Binary) => {
+                return Err(darling::Error::too_few_items(1).with_span(list));
+            }
+            _ => {
+                return Err(darling::Error::custom(format!(
+                    "`{}` is unary, so it takes no rhs types",
+                    info.trait_name,
+                ))
+                .with_span(item));
+            }
+        };
+
+        Ok(ForwardedOp {
+            path: path.clone(),
+            info,
+            rhs_types,
+        })
+    }
+}
+
+impl Parse for Newtype {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Newtype::try_from(input.parse::<DeriveInput>()?)
+    }
+}
+
+impl TryFrom<DeriveInput> for Newtype {
+    type Error = syn::Error;
+
+    fn try_from(input: DeriveInput) -> syn::Result<Self> {
+        let fields = match input.data {
+            Data::Struct(data) => data.fields,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "`Ops` can only be derived for structs",
+                ))
+            }
+        };
+
+        let field = match fields.iter().collect::<Vec<_>>()[..] {
+            [field] => field,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    format!(
+                        "`Ops` forwards to a single field, but `{}` has {} fields",
+                        input.ident,
+                        fields.len(),
+                    ),
+                ))
+            }
+        };
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(0.into()),
+        };
+
+        Ok(Newtype {
+            member,
+            field_ty: field.ty.clone(),
+            attrs: input.attrs,
+            ident: input.ident,
+            generics: input.generics,
+        })
+    }
+}
@@ -0,0 +1,116 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_quote, Member, Type};
+
+use crate::{catalog::OpInfo, utils::add_bound, BinOpImpl, Newtype, UnOpImpl};
+
+impl Newtype {
+    /// Returns the newtype applied to its own parameters, e.g., `W<T>`.
+    pub fn ty(&self) -> Type {
+        let ident = &self.ident;
+        let (_, ty_generics, _) = self.generics.split_for_impl();
+
+        parse_quote!(#ident #ty_generics)
+    }
+
+    /// Returns an expression wrapping `expr` into the newtype, e.g., `W(expr)`.
+    pub fn wrap(&self, expr: TokenStream) -> TokenStream {
+        let ident = &self.ident;
+        match &self.member {
+            Member::Named(field) => quote!(#ident { #field: #expr }),
+            Member::Unnamed(_) => quote!(#ident(#expr)),
+        }
+    }
+
+    /// Returns an implementation of the binary operation `op` between the newtype and `rhs_ty`,
+    /// which applies `op` to the field and wraps the result; if `rhs_ty` is `Self`, the rhs is
+    /// the newtype and `op` applies to both fields.
+    ///
+    /// The field type is bound by `op` if it involves generic parameters.
+    ///
+    /// In other words, for `struct W<T>(T)` and `rhs_ty = Self`, this produces the implementation
+    /// ```ignore
+    /// impl<T> Op<W<T>> for W<T>
+    /// where
+    ///     T: Op<T, Output = T>,
+    /// {
+    ///     type Output = W<T>;
+    ///
+    ///     fn op(self, rhs: W<T>) -> W<T> {
+    ///         W(Op::op(self.0, rhs.0))
+    ///     }
+    /// }
+    /// ```
+    pub fn binop_impl(&self, op: &OpInfo, rhs_ty: &Type) -> BinOpImpl {
+        let ty = self.ty();
+        let field_ty = &self.field_ty;
+        let member = &self.member;
+        let (rhs_ty, field_rhs_ty, rhs_expr) = if rhs_ty == &parse_quote!(Self) {
+            (ty.clone(), field_ty.clone(), quote!(rhs.#member))
+        } else {
+            (rhs_ty.clone(), rhs_ty.clone(), quote!(rhs))
+        };
+
+        let trait_ = op.path();
+        let fn_ident = format_ident!("{}", op.method);
+        let mut generics = self.generics.clone();
+        add_bound(
+            &mut generics,
+            field_ty,
+            quote!(#trait_<#field_rhs_ty, Output = #field_ty>),
+        );
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        let body = self.wrap(quote!(#trait_::#fn_ident(self.#member, #rhs_expr)));
+
+        parse_quote! {
+            impl #impl_generics #trait_<#rhs_ty> for #ty #where_clause {
+                type Output = #ty;
+
+                fn #fn_ident(self, rhs: #rhs_ty) -> #ty {
+                    #body
+                }
+            }
+        }
+    }
+
+    /// Returns an implementation of the unary operation `op` on the newtype, which applies `op`
+    /// to the field and wraps the result.
+    ///
+    /// The field type is bound by `op` if it involves generic parameters.
+    ///
+    /// In other words, for `struct W<T>(T)`, this produces the implementation
+    /// ```ignore
+    /// impl<T> Op for W<T>
+    /// where
+    ///     T: Op<Output = T>,
+    /// {
+    ///     type Output = W<T>;
+    ///
+    ///     fn op(self) -> W<T> {
+    ///         W(Op::op(self.0))
+    ///     }
+    /// }
+    /// ```
+    pub fn unop_impl(&self, op: &OpInfo) -> UnOpImpl {
+        let ty = self.ty();
+        let field_ty = &self.field_ty;
+        let member = &self.member;
+
+        let trait_ = op.path();
+        let fn_ident = format_ident!("{}", op.method);
+        let mut generics = self.generics.clone();
+        add_bound(&mut generics, field_ty, quote!(#trait_<Output = #field_ty>));
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        let body = self.wrap(quote!(#trait_::#fn_ident(self.#member)));
+
+        parse_quote! {
+            impl #impl_generics #trait_ for #ty #where_clause {
+                type Output = #ty;
+
+                fn #fn_ident(self) -> #ty {
+                    #body
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+mod parse;
+pub use parse::*;
+
+mod write;
+
+use crate::utils::print_tokens;
+
+impl OpTrait {
+    pub fn expand(&self, args: OpTraitArgs) -> TokenStream {
+        if args.dev_print {
+            dbg!(&args);
+            print_tokens("op_trait definition", self);
+        }
+
+        let assign_trait = self.assign_trait();
+        let ext_trait = self.ext_trait();
+        let ext_impl = self.ext_impl();
+        let free_fn = self.free_fn();
+
+        quote! {
+            #self
+            #assign_trait
+            #ext_trait
+            #ext_impl
+            #free_fn
+        }
+    }
+}
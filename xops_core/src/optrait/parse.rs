@@ -0,0 +1,81 @@
+use std::convert::TryFrom;
+
+use darling::FromMeta;
+use syn::{
+    parse::{Parse, ParseStream},
+    ItemTrait, TraitItem, TraitItemMethod,
+};
+
+use crate::utils::position_by_role;
+
+// structs -----------------------------------------------------------------------------------------
+
+/// Arguments for the macro `#[op_trait(...)]`
+#[derive(Clone, Default, FromMeta, Debug)]
+#[darling(default)]
+pub struct OpTraitArgs {
+    pub dev_print: bool,
+}
+
+/// A trait definition for a custom binary operation, laid out like the traits of `std::ops`.
+///
+/// The operator method is found like the one of a [`BinOpImpl`](crate::BinOpImpl): it takes a
+/// receiver and one more argument, and is named after the trait if there are several such
+/// methods.
+///
+/// Example:
+/// ```ignore
+/// pub trait Dot<Rhs = Self> {
+///     type Output;
+///
+///     fn dot(self, rhs: Rhs) -> Self::Output;
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct OpTrait {
+    pub item: ItemTrait,
+    pub item_fn: TraitItemMethod,
+}
+
+// parsing -----------------------------------------------------------------------------------------
+
+impl Parse for OpTrait {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        OpTrait::try_from(input.parse::<ItemTrait>()?)
+    }
+}
+
+impl TryFrom<ItemTrait> for OpTrait {
+    type Error = syn::Error;
+
+    fn try_from(item: ItemTrait) -> syn::Result<Self> {
+        let op_fn = item.ident.to_string().to_lowercase();
+
+        let fn_pos = position_by_role(&item.items, |item| match item {
+            TraitItem::Method(method)
+                if method.sig.inputs.len() == 2 && method.sig.receiver().is_some() =>
+            {
+                Some(method.sig.ident == op_fn)
+            }
+            _ => None,
+        })
+        .ok_or_else(|| {
+            syn::Error::new(
+                item.brace_token.span,
+                "expected the operator method, as in `fn op(self, rhs: Rhs) -> Self::Output;`",
+            )
+        })?;
+        let item_fn = match &item.items[fn_pos] {
+            TraitItem::Method(item_fn) => item_fn.clone(),
+            _ => unreachable!(),
+        };
+        if !item_fn.sig.generics.params.is_empty() {
+            return Err(syn::Error::new_spanned(
+                &item_fn.sig.generics,
+                "the operator method cannot have generic parameters",
+            ));
+        }
+
+        Ok(OpTrait { item, item_fn })
+    }
+}
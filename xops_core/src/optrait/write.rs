@@ -0,0 +1,250 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use syn::{parse_quote, FnArg, GenericParam, Generics, Ident, ItemFn, ItemImpl, ItemTrait, Type};
+
+use crate::{
+    utils::{fresh_type_param, resolve_self},
+    OpTrait,
+};
+
+impl OpTrait {
+    /// Returns the type of the receiver in terms of `Self`, e.g., `&Self` for `&self`.
+    pub fn lhs_arg_ty(&self) -> Type {
+        match self.item_fn.sig.receiver() {
+            Some(FnArg::Receiver(receiver)) => match &receiver.reference {
+                Some((_, lifetime)) => {
+                    let mutability = &receiver.mutability;
+                    parse_quote!(&#lifetime #mutability Self)
+                }
+                None => parse_quote!(Self),
+            },
+            Some(FnArg::Typed(pat_ty)) => (*pat_ty.ty).clone(),
+            None => unreachable!("the operator method has a receiver"),
+        }
+    }
+
+    /// Returns the type of the method's second argument, e.g., `Rhs` for `rhs: Rhs`.
+    pub fn rhs_arg_ty(&self) -> &Type {
+        match self.item_fn.sig.inputs.iter().nth(1) {
+            Some(FnArg::Typed(pat_ty)) => &pat_ty.ty,
+            _ => unreachable!("the operator method has a second argument"),
+        }
+    }
+
+    /// Checks whether the method takes both operands by value, as the traits of `std::ops` do.
+    pub fn operands_by_value(&self) -> bool {
+        let by_value = |ty: &Type| !matches!(ty, Type::Reference(_));
+
+        by_value(&self.lhs_arg_ty()) && by_value(self.rhs_arg_ty())
+    }
+
+    /// Returns the trait applied to its own parameters, as in `Dot<Rhs>`.
+    fn trait_ty(&self) -> TokenStream {
+        let ident = &self.item.ident;
+        let (_, ty_generics, _) = self.item.generics.split_for_impl();
+
+        quote!(#ident #ty_generics)
+    }
+
+    /// Returns the parameters of the trait together with a fresh type parameter `Lhs` for the
+    /// implementing type, which is bound by the trait; the defaults of the parameters are kept.
+    fn generics_with_lhs(&self, lhs: &Ident) -> Generics {
+        let trait_ty = self.trait_ty();
+        let mut generics = resolve_self(&self.item.generics, &parse_quote!(#lhs));
+        generics.where_clause = self
+            .item
+            .generics
+            .where_clause
+            .as_ref()
+            .map(|where_clause| resolve_self(where_clause, &parse_quote!(#lhs)));
+
+        let lifetimes = generics.lifetimes().count();
+        generics
+            .params
+            .insert(lifetimes, GenericParam::Type(parse_quote!(#lhs)));
+        generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote!(#lhs: #trait_ty));
+
+        generics
+    }
+
+    /// Returns the compound assignment counterpart of the trait, which is what
+    /// `#[binop(assign)]` implements for this trait.
+    ///
+    /// In other words, if `self` is of the form
+    /// ```ignore
+    /// pub trait Op<Rhs = Self> {
+    ///     type Output;
+    ///
+    ///     fn op(self, rhs: Rhs) -> Self::Output;
+    /// }
+    /// ```
+    /// then this produces the trait
+    /// ```ignore
+    /// pub trait OpAssign<Rhs = Self> {
+    ///     fn op_assign(&mut self, rhs: Rhs);
+    /// }
+    /// ```
+    pub fn assign_trait(&self) -> ItemTrait {
+        let vis = &self.item.vis;
+        let generics = &self.item.generics;
+        let where_clause = &generics.where_clause;
+        let ident = &self.item.ident;
+        let fn_ident = &self.item_fn.sig.ident;
+        let assign_ident = format_ident!("{}Assign", ident);
+        let assign_fn = format_ident!("{}_assign", fn_ident);
+        let rhs_arg_ty = self.rhs_arg_ty();
+
+        let doc = format!(
+            "The compound assignment counterpart of [`{}`], where `a.{}(b)` performs \
+             `a = a.{}(b)`.",
+            ident, assign_fn, fn_ident,
+        );
+
+        parse_quote! {
+            #[doc = #doc]
+            #vis trait #assign_ident #generics #where_clause {
+                fn #assign_fn(&mut self, rhs: #rhs_arg_ty);
+            }
+        }
+    }
+
+    /// Returns an extension trait providing the operation on borrowed operands, which are
+    /// cloned, if the method takes both operands by value; otherwise this returns `None`.
+    ///
+    /// In other words, if `self` is of the form
+    /// ```ignore
+    /// pub trait Op<Rhs = Self> {
+    ///     type Output;
+    ///
+    ///     fn op(self, rhs: Rhs) -> Self::Output;
+    /// }
+    /// ```
+    /// then this produces the trait
+    /// ```ignore
+    /// pub trait OpExt<Rhs = Self>: Op<Rhs> {
+    ///     fn op_ref(&self, rhs: &Rhs) -> Self::Output
+    ///     where
+    ///         Self: Clone,
+    ///         Rhs: Clone,
+    ///     {
+    ///         <Self as Op<Rhs>>::op(self.clone(), rhs.clone())
+    ///     }
+    /// }
+    /// ```
+    /// which is implemented for every implementor of `Op` by [`OpTrait::ext_impl`].
+    pub fn ext_trait(&self) -> Option<ItemTrait> {
+        if !self.operands_by_value() {
+            return None;
+        }
+
+        let vis = &self.item.vis;
+        let generics = &self.item.generics;
+        let where_clause = &generics.where_clause;
+        let ident = &self.item.ident;
+        let fn_ident = &self.item_fn.sig.ident;
+        let ext_ident = format_ident!("{}Ext", ident);
+        let ref_fn = format_ident!("{}_ref", fn_ident);
+        let rhs_arg_ty = self.rhs_arg_ty();
+        let out = &self.item_fn.sig.output;
+        let trait_ty = self.trait_ty();
+
+        let doc = format!("Extension methods for [`{}`].", ident);
+        let fn_doc = format!(
+            "Performs [`{}::{}`] on clones of the borrowed operands.",
+            ident, fn_ident,
+        );
+
+        Some(parse_quote! {
+            #[doc = #doc]
+            #vis trait #ext_ident #generics: #trait_ty #where_clause {
+                #[doc = #fn_doc]
+                fn #ref_fn(&self, rhs: &#rhs_arg_ty) #out
+                where
+                    Self: ::core::clone::Clone,
+                    #rhs_arg_ty: ::core::clone::Clone,
+                {
+                    <Self as #trait_ty>::#fn_ident(
+                        ::core::clone::Clone::clone(self),
+                        ::core::clone::Clone::clone(rhs),
+                    )
+                }
+            }
+        })
+    }
+
+    /// Returns the blanket implementation of the trait given by [`OpTrait::ext_trait`], if any.
+    pub fn ext_impl(&self) -> Option<ItemImpl> {
+        if !self.operands_by_value() {
+            return None;
+        }
+
+        let ext_ident = format_ident!("{}Ext", self.item.ident);
+        let lhs = fresh_type_param(&self.item.generics, "Lhs");
+        let generics = self.generics_with_lhs(&lhs);
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        let (_, ty_generics, _) = self.item.generics.split_for_impl();
+
+        Some(parse_quote! {
+            impl #impl_generics #ext_ident #ty_generics for #lhs #where_clause {}
+        })
+    }
+
+    /// Returns a free function performing the operation, named like the method.
+    ///
+    /// In other words, if `self` is of the form
+    /// ```ignore
+    /// pub trait Op<Rhs = Self> {
+    ///     type Output;
+    ///
+    ///     fn op(self, rhs: Rhs) -> Self::Output;
+    /// }
+    /// ```
+    /// then this produces the function
+    /// ```ignore
+    /// pub fn op<Lhs, Rhs>(lhs: Lhs, rhs: Rhs) -> <Lhs>::Output
+    /// where
+    ///     Lhs: Op<Rhs>,
+    /// {
+    ///     <Lhs as Op<Rhs>>::op(lhs, rhs)
+    /// }
+    /// ```
+    pub fn free_fn(&self) -> ItemFn {
+        let vis = &self.item.vis;
+        let ident = &self.item.ident;
+        let fn_ident = &self.item_fn.sig.ident;
+        let lhs = fresh_type_param(&self.item.generics, "Lhs");
+        let lhs_ty: Type = parse_quote!(#lhs);
+        let generics = self.generics_with_lhs(&lhs);
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        let trait_ty = self.trait_ty();
+
+        let lhs_arg_ty = resolve_self(&self.lhs_arg_ty(), &lhs_ty);
+        let rhs_arg_ty = resolve_self(self.rhs_arg_ty(), &lhs_ty);
+        let out = resolve_self(&self.item_fn.sig.output, &lhs_ty);
+
+        let doc = format!(
+            "Performs [`{}::{}`], as in `{}(a, b)` for `a.{}(b)`.",
+            ident, fn_ident, fn_ident, fn_ident,
+        );
+
+        parse_quote! {
+            #[doc = #doc]
+            #vis fn #fn_ident #impl_generics(lhs: #lhs_arg_ty, rhs: #rhs_arg_ty) #out
+            #where_clause
+            {
+                <#lhs as #trait_ty>::#fn_ident(lhs, rhs)
+            }
+        }
+    }
+}
+
+// impl ToTokens -----------------------------------------------------------------------------------
+
+impl ToTokens for OpTrait {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.item.to_tokens(tokens);
+    }
+}
@@ -0,0 +1,119 @@
+use darling::util::SpannedValue;
+use proc_macro2::TokenStream;
+use quote::quote;
+
+mod parse;
+pub use parse::*;
+
+mod write;
+
+use crate::{
+    utils::{pretty, print_tokens, TypeConversion},
+    Error, Result,
+};
+
+impl UnOpImpl {
+    pub fn expand(&self, args: UnOpArgs) -> TokenStream {
+        if let Err(err) = check(&args, self) {
+            let err = err.to_compile_error();
+            return quote!(#self #err);
+        }
+
+        expand(&args, self.resolve_self())
+    }
+}
+
+/// Checks that every argument applies to the given implementation `impl_`.
+fn check(args: &UnOpArgs, impl_: &UnOpImpl) -> Result<()> {
+    let mut errors = Vec::new();
+
+    if *args.refs_clone && *args.refs_copy {
+        errors.push(Error::new(
+            args.refs_copy.span(),
+            "`refs_clone` and `refs_copy` each derive the reference form, so together they would \
+             derive it twice; keep one of them",
+        ));
+    }
+
+    if *args.derefs {
+        if let Some(refs) = [&args.refs_clone, &args.refs_copy].iter().copied().find(|refs| ***refs) {
+            errors.push(Error::new(
+                refs.span(),
+                "the reference form derived from an owned operand cannot be dereferenced again; \
+                 keep either this argument or `derefs`",
+            ));
+        } else if impl_.ty.as_deref().is_none() {
+            errors.push(Error::new(
+                args.derefs.span(),
+                format!(
+                    "`derefs` needs a reference operand; `{}` is owned",
+                    pretty(&impl_.ty)
+                ),
+            ));
+        }
+    }
+
+    Error::combine_all(errors)
+}
+
+fn expand(args: &UnOpArgs, impl_: UnOpImpl) -> TokenStream {
+    if args.dev_print {
+        dbg!(args);
+        print_tokens("unop impltn", &impl_);
+    }
+
+    if *args.refs_clone {
+        expand_refs_clone(args, impl_)
+    } else if *args.refs_copy {
+        expand_refs_copy(args, impl_)
+    } else if *args.derefs {
+        expand_derefs(args, impl_)
+    } else {
+        quote!(#impl_)
+    }
+}
+
+fn expand_refs_clone(args: &UnOpArgs, impl_: UnOpImpl) -> TokenStream {
+    let new_args = UnOpArgs {
+        refs_clone: SpannedValue::default(),
+        ..args.clone()
+    };
+
+    let expanded = expand(&new_args, impl_.clone());
+    let expanded_ref = expand(&new_args, impl_.ref_clone());
+
+    quote! {
+        #expanded
+        #expanded_ref
+    }
+}
+
+fn expand_refs_copy(args: &UnOpArgs, impl_: UnOpImpl) -> TokenStream {
+    let new_args = UnOpArgs {
+        refs_copy: SpannedValue::default(),
+        ..args.clone()
+    };
+
+    let expanded = expand(&new_args, impl_.clone());
+    let expanded_ref = expand(&new_args, impl_.ref_copy());
+
+    quote! {
+        #expanded
+        #expanded_ref
+    }
+}
+
+fn expand_derefs(args: &UnOpArgs, impl_: UnOpImpl) -> TokenStream {
+    let new_args = UnOpArgs {
+        derefs: SpannedValue::default(),
+        ..args.clone()
+    };
+
+    let expanded = expand(&new_args, impl_.clone());
+    let expanded_deref = impl_.try_deref().map(|deref| expand(&new_args, deref));
+
+    quote! {
+        #expanded
+        #expanded_deref
+    }
+}
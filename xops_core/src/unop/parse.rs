@@ -0,0 +1,234 @@
+use std::convert::TryFrom;
+
+use darling::{util::SpannedValue, FromMeta};
+use syn::{
+    parenthesized,
+    parse::{Parse, ParseStream},
+    token, Attribute, Block, FnArg, Generics, Ident, ImplItem, ImplItemMethod, ItemImpl, Path,
+    Receiver, ReturnType, Token, Type, WhereClause,
+};
+
+use crate::{
+    utils::{parse_fn_ident, position_by_role},
+    BinOpOutput,
+};
+
+// structs -----------------------------------------------------------------------------------------
+
+/// Arguments for the macro `#[unop(...)]`
+#[derive(Clone, Default, FromMeta, Debug)]
+#[darling(default)]
+pub struct UnOpArgs {
+    pub dev_print: bool,
+    pub refs_clone: SpannedValue<bool>,
+    pub refs_copy: SpannedValue<bool>,
+    pub derefs: SpannedValue<bool>,
+}
+
+/// Method implementation for a unary operation:
+/// `fn op(self) -> C { .. }`
+#[derive(Clone, Debug)]
+pub struct UnOpFn {
+    pub attrs: Vec<Attribute>,
+    pub fn_token: Token![fn],
+    pub ident: Ident,
+    pub paren_token: token::Paren,
+    pub arg: Receiver,
+    pub arrow_token: Token![->],
+    pub out_ty: Type,
+    pub where_clause: Option<WhereClause>,
+    pub block: Block,
+}
+
+/// An impl block for a unary operation.
+///
+/// This is the counterpart of [`BinOpImpl`](crate::BinOpImpl) for traits like `Neg` and `Not`,
+/// and is likewise built from a `syn::ItemImpl`, in which the output type and the operator
+/// method are found by their roles rather than their positions.
+///
+/// Example:
+/// ```ignore
+/// impl Neg for A {
+///     type Output = C;
+///
+///     fn neg(self) -> Self::Output {
+///         ...
+///     }
+/// }
+/// ```
+///
+/// The output type is the associated type `Output`, or else the only associated type; the
+/// operator method is the method taking only `self` whose name is the lowercased trait name, or
+/// else the only such method. Any other items are kept in `items` and copied into derived
+/// implementations.
+#[derive(Clone, Debug)]
+pub struct UnOpImpl {
+    pub attrs: Vec<Attribute>,
+    pub defaultness: Option<Token![default]>,
+    pub unsafety: Option<Token![unsafe]>,
+    pub impl_token: Token![impl],
+    pub generics: Generics,
+    pub trait_: Path,
+    pub for_token: Token![for],
+    pub ty: Type,
+    pub brace_token: token::Brace,
+    pub item_out: BinOpOutput,
+    pub item_fn: UnOpFn,
+    pub items: Vec<ImplItem>,
+}
+
+// impl Parse --------------------------------------------------------------------------------------
+
+impl Parse for UnOpFn {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        Ok(UnOpFn {
+            attrs: input.call(Attribute::parse_outer)?,
+            fn_token: input.parse()?,
+            ident: input.call(parse_fn_ident)?,
+            paren_token: parenthesized!(content in input),
+            arg: content.parse()?,
+            arrow_token: input.parse()?,
+            out_ty: input.parse()?,
+            where_clause: input.parse()?,
+            block: input.parse()?,
+        })
+    }
+}
+
+impl Parse for UnOpImpl {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        UnOpImpl::try_from(input.parse::<ItemImpl>()?)
+    }
+}
+
+// impl TryFrom ------------------------------------------------------------------------------------
+
+impl TryFrom<ImplItemMethod> for UnOpFn {
+    type Error = syn::Error;
+
+    fn try_from(item: ImplItemMethod) -> syn::Result<Self> {
+        let sig = item.sig;
+        if !sig.generics.params.is_empty() {
+            return Err(syn::Error::new_spanned(
+                &sig.generics.params,
+                "operator methods take no generic parameters; declare them on the impl instead",
+            ));
+        }
+
+        let mut inputs = sig.inputs.into_iter();
+        let arg = match (inputs.next(), inputs.next()) {
+            (Some(FnArg::Receiver(arg)), None) => arg,
+            (Some(arg), None) => {
+                return Err(syn::Error::new_spanned(arg, "expected the receiver `self`"))
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    sig.paren_token.span,
+                    "expected only `self`, as in `(self)`",
+                ))
+            }
+        };
+
+        let (arrow_token, out_ty) = match sig.output {
+            ReturnType::Type(arrow_token, out_ty) => (arrow_token, *out_ty),
+            ReturnType::Default => {
+                return Err(syn::Error::new(
+                    sig.paren_token.span,
+                    "expected the return type `-> Self::Output`",
+                ))
+            }
+        };
+
+        Ok(UnOpFn {
+            attrs: item.attrs,
+            fn_token: sig.fn_token,
+            ident: sig.ident,
+            paren_token: sig.paren_token,
+            arg,
+            arrow_token,
+            out_ty,
+            where_clause: sig.generics.where_clause,
+            block: item.block,
+        })
+    }
+}
+
+impl TryFrom<ItemImpl> for UnOpImpl {
+    type Error = syn::Error;
+
+    fn try_from(item: ItemImpl) -> syn::Result<Self> {
+        let (trait_, for_token) = match item.trait_ {
+            Some((None, trait_, for_token)) => (trait_, for_token),
+            Some((Some(bang), ..)) => {
+                return Err(syn::Error::new_spanned(
+                    bang,
+                    "negative impls are not supported",
+                ))
+            }
+            None => {
+                return Err(syn::Error::new_spanned(
+                    &item.self_ty,
+                    "expected an operator trait implementation, as in `impl Neg for A`",
+                ))
+            }
+        };
+
+        let op_fn = trait_
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string().to_lowercase())
+            .unwrap_or_default();
+        let brace_span = item.brace_token.span;
+        let mut items = item.items;
+
+        let out_pos = position_by_role(&items, |item| match item {
+            ImplItem::Type(item_out) => Some(item_out.ident == "Output"),
+            _ => None,
+        })
+        .ok_or_else(|| {
+            syn::Error::new(
+                brace_span,
+                "expected the output type, as in `type Output = C;`",
+            )
+        })?;
+        let item_out = match items.remove(out_pos) {
+            ImplItem::Type(item_out) => BinOpOutput::try_from(item_out)?,
+            _ => unreachable!(),
+        };
+
+        let fn_pos = position_by_role(&items, |item| match item {
+            ImplItem::Method(method)
+                if method.sig.inputs.len() == 1 && method.sig.receiver().is_some() =>
+            {
+                Some(method.sig.ident == op_fn)
+            }
+            _ => None,
+        })
+        .ok_or_else(|| {
+            syn::Error::new(
+                brace_span,
+                "expected the operator method, as in `fn neg(self) -> Self::Output`",
+            )
+        })?;
+        let item_fn = match items.remove(fn_pos) {
+            ImplItem::Method(item_fn) => UnOpFn::try_from(item_fn)?,
+            _ => unreachable!(),
+        };
+
+        Ok(UnOpImpl {
+            attrs: item.attrs,
+            defaultness: item.defaultness,
+            unsafety: item.unsafety,
+            impl_token: item.impl_token,
+            generics: item.generics,
+            trait_,
+            for_token,
+            ty: *item.self_ty,
+            brace_token: item.brace_token,
+            item_out,
+            item_fn,
+            items,
+        })
+    }
+}
@@ -0,0 +1,201 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens, TokenStreamExt};
+use syn::{parse_quote, Type};
+
+use crate::{
+    utils::{
+        add_bound, allow_named_lifetimes, mentions_param, name_lifetime, named_lifetimes,
+        prune_generics, resolve_self, TypeConversion,
+    },
+    UnOpFn, UnOpImpl,
+};
+
+impl UnOpImpl {
+    /// Returns `self` with each `Self` in the output type, the where clause, and the extra items
+    /// replaced by `ty`.
+    ///
+    /// Derived implementations copy these tokens into impls whose `Self` is a different type, so
+    /// every derivation should start from a resolved implementation.
+    pub fn resolve_self(&self) -> Self {
+        let ty = &self.ty;
+        let mut resolved = self.clone();
+
+        resolved.item_out.ty = resolve_self(&self.item_out.ty, ty);
+        resolved.generics.where_clause = self
+            .generics
+            .where_clause
+            .as_ref()
+            .map(|where_clause| resolve_self(where_clause, ty));
+        resolved.items = self
+            .items
+            .iter()
+            .map(|item| resolve_self(item, ty))
+            .collect();
+
+        resolved
+    }
+
+    /// If `ty = &A`, this returns an implementation of `op A` utilizing `op &A`; for
+    /// `ty = &mut A`, the new method takes `mut self` and borrows it mutably instead.
+    ///
+    /// If `ty` is not a reference type, or the output type mentions a lifetime which only `ty`
+    /// constrains, this returns `None`.
+    ///
+    /// In other words, if `self` is of the form
+    /// ```ignore
+    /// impl Op for &A {
+    ///     ...
+    /// }
+    /// ```
+    /// then this produces the implementation
+    /// ```ignore
+    /// impl Op for A {
+    ///     ...
+    ///     fn op(self) -> Self::Output {
+    ///         (&self).op()
+    ///     }
+    /// }
+    /// ```
+    pub fn try_deref(&self) -> Option<Self> {
+        let ty = self.ty.as_deref()?;
+
+        let derived = if self.ty.is_mut_ref() {
+            let mut derived = self.delegate(ty, quote!((&mut self)));
+            derived.item_fn.arg.mutability = Some(Default::default());
+            derived
+        } else {
+            self.delegate(ty, quote!((&self)))
+        };
+        let (ty, out_ty) = (&derived.ty, derived.item_out.ty.to_token_stream());
+        let unconstrained = derived.generics.params.iter().any(|param| {
+            mentions_param(out_ty.clone(), param) && !mentions_param(quote!(#ty), param)
+        });
+
+        (!unconstrained).then_some(derived)
+    }
+
+    /// Returns an implementation of `op &A` utilizing `op A`.
+    ///
+    /// This needs `A: Clone`, which is added to the where clause if `A` involves generic
+    /// parameters.
+    ///
+    /// In other words, if `self` is of the form
+    /// ```ignore
+    /// impl Op for A {
+    ///     ...
+    /// }
+    /// ```
+    /// then this produces the implementation
+    /// ```ignore
+    /// impl Op for &A {
+    ///     ...
+    ///     fn op(self) -> Self::Output {
+    ///         self.clone().op()
+    ///     }
+    /// }
+    /// ```
+    pub fn ref_clone(&self) -> Self {
+        let ty = self.ty.as_ref();
+
+        let mut derived = self.delegate(ty, quote!(self.clone()));
+        let clone_bound = quote!(::core::clone::Clone);
+        add_bound(&mut derived.generics, &self.ty, clone_bound);
+        derived
+    }
+
+    /// Returns an implementation of `op &A` utilizing `op A`.
+    ///
+    /// This needs `A: Copy`, which is added to the where clause if `A` involves generic
+    /// parameters.
+    ///
+    /// In other words, if `self` is of the form
+    /// ```ignore
+    /// impl Op for A {
+    ///     ...
+    /// }
+    /// ```
+    /// then this produces the implementation
+    /// ```ignore
+    /// impl Op for &A {
+    ///     ...
+    ///     fn op(self) -> Self::Output {
+    ///         (*self).op()
+    ///     }
+    /// }
+    /// ```
+    pub fn ref_copy(&self) -> Self {
+        let ty = self.ty.as_ref();
+
+        let mut derived = self.delegate(ty, quote!((*self)));
+        let copy_bound = quote!(::core::marker::Copy);
+        add_bound(&mut derived.generics, &self.ty, copy_bound);
+        derived
+    }
+
+    /// Returns an implementation of `op ty` whose method evaluates `expr.op()`, where `self` is
+    /// the operand of the new method.
+    ///
+    /// A reference operand with an elided lifetime gets a fresh named lifetime `'a`, and generic
+    /// parameters which the new implementation no longer mentions are pruned.
+    fn delegate(&self, ty: Type, expr: TokenStream) -> Self {
+        let mut generics = self.generics.clone();
+        let ty = name_lifetime(ty, &mut generics, "a");
+        let named = named_lifetimes(&self.generics, &generics);
+        let fn_ident = &self.item_fn.ident;
+        let item_fn = parse_quote! {
+            fn #fn_ident(self) -> Self::Output {
+                #expr.#fn_ident()
+            }
+        };
+
+        let item_out = &self.item_out;
+        let items = &self.items;
+        prune_generics(&mut generics, &quote!(#ty #item_out #item_fn #(#items)*));
+
+        let mut derived = UnOpImpl {
+            generics,
+            ty,
+            item_fn,
+            ..self.clone()
+        };
+        let tokens = derived.to_token_stream();
+        allow_named_lifetimes(&mut derived.attrs, &named, &tokens);
+        derived
+    }
+}
+
+// impl ToTokens -----------------------------------------------------------------------------------
+
+impl ToTokens for UnOpFn {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.append_all(&self.attrs);
+        self.fn_token.to_tokens(tokens);
+        self.ident.to_tokens(tokens);
+        self.paren_token.surround(tokens, |tokens| {
+            self.arg.to_tokens(tokens);
+        });
+        self.arrow_token.to_tokens(tokens);
+        self.out_ty.to_tokens(tokens);
+        self.where_clause.to_tokens(tokens);
+        self.block.to_tokens(tokens);
+    }
+}
+
+impl ToTokens for UnOpImpl {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.append_all(&self.attrs);
+        self.defaultness.to_tokens(tokens);
+        self.unsafety.to_tokens(tokens);
+        self.impl_token.to_tokens(tokens);
+        self.generics.to_tokens(tokens);
+        self.trait_.to_tokens(tokens);
+        self.for_token.to_tokens(tokens);
+        self.ty.to_tokens(tokens);
+        self.generics.where_clause.to_tokens(tokens);
+        self.brace_token.surround(tokens, |tokens| {
+            self.item_out.to_tokens(tokens);
+            self.item_fn.to_tokens(tokens);
+            tokens.append_all(&self.items);
+        });
+    }
+}
@@ -1,6 +1,16 @@
+use proc_macro2::{Group, Spacing, Span, TokenStream, TokenTree};
 use quote::{quote};
 use quote::ToTokens;
-use syn::{Type, parse_quote};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_quote,
+    punctuated::Punctuated,
+    Attribute, Expr, GenericParam, Generics, Ident, Lifetime, Lit, Meta, NestedMeta, Token, Type,
+    TypeParamBound, WherePredicate,
+};
+
+/// the bounds of a type parameter or a where-predicate, as in `T: Clone + 'a`
+type Bounds = Punctuated<TypeParamBound, Token![+]>;
 
 /// quotes and prints `item` under the label `header`
 ///
@@ -9,11 +19,445 @@ pub fn print_tokens<T: ToTokens>(header: &str, item: T) {
     println!("BEGIN {} \n{}\nEND\n", header, quote!(#item));
 }
 
+/// checks whether `item` mentions any of the type or const parameters declared in `generics`
+pub fn mentions_generics<T: ToTokens>(item: &T, generics: &Generics) -> bool {
+    let idents: Vec<&Ident> = generics
+        .type_params()
+        .map(|param| &param.ident)
+        .chain(generics.const_params().map(|param| &param.ident))
+        .collect();
+
+    !idents.is_empty() && mentions_any(item.to_token_stream(), &idents)
+}
+
+/// adds the where-predicate `ty: bound` to `generics` if `ty` involves any of its parameters
+///
+/// predicates on types without parameters are left out, as rustc would check them anyway
+pub fn add_bound<T: ToTokens>(generics: &mut Generics, ty: &Type, bound: T) {
+    if mentions_generics(ty, generics) {
+        generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote!(#ty: #bound));
+    }
+}
+
+/// returns a lifetime named after `name` which is not yet declared in `generics`
+pub fn fresh_lifetime(generics: &Generics, name: &str) -> Lifetime {
+    let taken: Vec<String> = generics
+        .lifetimes()
+        .map(|def| def.lifetime.ident.to_string())
+        .collect();
+
+    Lifetime {
+        apostrophe: Span::call_site(),
+        ident: fresh_ident(&taken, name),
+    }
+}
+
+/// names the lifetime of `ty` if it is a reference with an elided lifetime, as in `&A`, by
+/// declaring a fresh lifetime after `name` in `generics`, following the lifetimes already declared
+pub fn name_lifetime(ty: Type, generics: &mut Generics, name: &str) -> Type {
+    match ty {
+        Type::Reference(mut ref_ty) if ref_ty.lifetime.is_none() => {
+            let lifetime = fresh_lifetime(generics, name);
+            let index = generics.lifetimes().count();
+            generics.params.insert(index, parse_quote!(#lifetime));
+            ref_ty.lifetime = Some(lifetime);
+            Type::Reference(ref_ty)
+        }
+        ty => ty,
+    }
+}
+
+/// returns the lifetimes which [`name_lifetime`] declared in `generics` beyond those of `original`
+pub fn named_lifetimes(original: &Generics, generics: &Generics) -> Vec<Lifetime> {
+    generics
+        .lifetimes()
+        .skip(original.lifetimes().count())
+        .map(|def| def.lifetime.clone())
+        .collect()
+}
+
+/// allows the lifetimes `named` by [`name_lifetime`] on the impl `item` with attributes `attrs`,
+/// if clippy would ask the macro user to elide one of them, that is, if `item` uses it only once
+/// besides declaring it
+pub fn allow_named_lifetimes<T: ToTokens>(
+    attrs: &mut Vec<Attribute>,
+    named: &[Lifetime],
+    item: &T,
+) {
+    let tokens = item.to_token_stream();
+    let needless = named
+        .iter()
+        .any(|lifetime| count_lifetime(tokens.clone(), &lifetime.ident) <= 2);
+    let allow: Attribute = parse_quote!(#[allow(clippy::needless_lifetimes)]);
+    if needless && !attrs.iter().any(|attr| tokens_eq(attr, &allow)) {
+        attrs.push(allow);
+    }
+}
+
+/// removes the parameters of `generics` which `item` no longer mentions, along with the
+/// where-predicates and outlives bounds on them
+///
+/// a parameter is kept if `item` mentions it, or if a trait bound of a kept parameter or a
+/// where-predicate on a kept parameter does; where-predicates on no parameter at all are kept
+pub fn prune_generics<T: ToTokens>(generics: &mut Generics, item: &T) {
+    let params: Vec<GenericParam> = generics.params.iter().cloned().collect();
+    let predicates: Vec<WherePredicate> = match &generics.where_clause {
+        Some(where_clause) => where_clause.predicates.iter().cloned().collect(),
+        None => Vec::new(),
+    };
+    let mentioned = |tokens: TokenStream| -> Vec<bool> {
+        params
+            .iter()
+            .map(|param| mentions_param(tokens.clone(), param))
+            .collect()
+    };
+    let trait_bounds = |bounds: &Bounds| {
+        let bounds = bounds
+            .iter()
+            .filter(|bound| matches!(bound, TypeParamBound::Trait(_)));
+        quote!(#(#bounds)*)
+    };
+    let anchored = |predicate: &WherePredicate, live: &[bool]| {
+        let on = match predicate {
+            WherePredicate::Type(predicate) => mentioned(predicate.bounded_ty.to_token_stream()),
+            WherePredicate::Lifetime(predicate) => mentioned(predicate.lifetime.to_token_stream()),
+            WherePredicate::Eq(_) => return true,
+        };
+        !on.contains(&true) || on.iter().zip(live).any(|(on, live)| *on && *live)
+    };
+
+    let mut live = mentioned(item.to_token_stream());
+    loop {
+        let mut next = live.clone();
+        let param_reasons =
+            params
+                .iter()
+                .zip(&live)
+                .filter(|(_, live)| **live)
+                .map(|(param, _)| match param {
+                    GenericParam::Type(param) => {
+                        let bounds = trait_bounds(&param.bounds);
+                        let default = &param.default;
+                        quote!(#bounds #default)
+                    }
+                    GenericParam::Const(param) => param.to_token_stream(),
+                    GenericParam::Lifetime(_) => TokenStream::new(),
+                });
+        let predicate_reasons = predicates
+            .iter()
+            .filter(|predicate| anchored(predicate, &live))
+            .map(|predicate| match predicate {
+                WherePredicate::Type(predicate) => {
+                    let bounded_ty = &predicate.bounded_ty;
+                    let bounds = trait_bounds(&predicate.bounds);
+                    quote!(#bounded_ty #bounds)
+                }
+                WherePredicate::Lifetime(_) => TokenStream::new(),
+                WherePredicate::Eq(predicate) => predicate.to_token_stream(),
+            });
+        for reason in param_reasons.chain(predicate_reasons) {
+            for (next, on) in next.iter_mut().zip(mentioned(reason)) {
+                *next |= on;
+            }
+        }
+
+        if next == live {
+            break;
+        }
+        live = next;
+    }
+
+    let dead: Vec<&Lifetime> = params
+        .iter()
+        .zip(&live)
+        .filter_map(|(param, live)| match param {
+            GenericParam::Lifetime(def) if !live => Some(&def.lifetime),
+            _ => None,
+        })
+        .collect();
+    let outlives_live = |bound: &TypeParamBound| match bound {
+        TypeParamBound::Lifetime(lifetime) => !dead.contains(&lifetime),
+        TypeParamBound::Trait(_) => true,
+    };
+
+    generics.params = params
+        .iter()
+        .zip(&live)
+        .filter(|(_, live)| **live)
+        .map(|(param, _)| match param.clone() {
+            GenericParam::Type(mut param) => {
+                param.bounds = param.bounds.into_iter().filter(outlives_live).collect();
+                GenericParam::Type(param)
+            }
+            GenericParam::Lifetime(mut def) => {
+                def.bounds = def
+                    .bounds
+                    .into_iter()
+                    .filter(|lifetime| !dead.contains(&lifetime))
+                    .collect();
+                GenericParam::Lifetime(def)
+            }
+            param => param,
+        })
+        .collect();
+    let predicates: Vec<WherePredicate> = predicates
+        .iter()
+        .filter(|predicate| anchored(predicate, &live))
+        .cloned()
+        .filter_map(|predicate| match predicate {
+            WherePredicate::Type(mut predicate) => {
+                predicate.bounds = predicate.bounds.into_iter().filter(outlives_live).collect();
+                (!predicate.bounds.is_empty()).then_some(WherePredicate::Type(predicate))
+            }
+            WherePredicate::Lifetime(mut predicate) => {
+                predicate.bounds = predicate
+                    .bounds
+                    .into_iter()
+                    .filter(|lifetime| !dead.contains(&lifetime))
+                    .collect();
+                (!predicate.bounds.is_empty()).then_some(WherePredicate::Lifetime(predicate))
+            }
+            predicate => Some(predicate),
+        })
+        .collect();
+    generics.where_clause = match generics.where_clause.take() {
+        Some(mut where_clause) if !predicates.is_empty() => {
+            where_clause.predicates = predicates.into_iter().collect();
+            Some(where_clause)
+        }
+        _ => None,
+    };
+}
+
+/// returns a type parameter named after `name` which is not yet declared in `generics`
+pub fn fresh_type_param(generics: &Generics, name: &str) -> Ident {
+    let taken: Vec<String> = generics
+        .type_params()
+        .map(|param| param.ident.to_string())
+        .collect();
+
+    fresh_ident(&taken, name)
+}
+
+fn fresh_ident(taken: &[String], name: &str) -> Ident {
+    let ident = (0..)
+        .map(|i| match i {
+            0 => name.to_string(),
+            _ => format!("{}{}", name, i),
+        })
+        .find(|ident| !taken.contains(ident))
+        .unwrap();
+
+    Ident::new(&ident, Span::call_site())
+}
+
+/// parses the string literal `"EXPR"` as an expression
+pub fn expr_from_lit(lit: &Lit) -> darling::Result<Box<Expr>> {
+    match lit {
+        Lit::Str(expr) => expr
+            .parse()
+            .map(Box::new)
+            .map_err(|e| darling::Error::custom(e).with_span(expr)),
+        lit => Err(darling::Error::unexpected_lit_type(lit)),
+    }
+}
+
+/// parses the meta item `name = "EXPR"` as an expression, for use with `#[darling(with)]`
+pub fn expr_from_meta(meta: &Meta) -> darling::Result<Box<Expr>> {
+    match meta {
+        Meta::NameValue(nv) => expr_from_lit(&nv.lit),
+        _ => Err(darling::Error::unsupported_format("word or list")),
+    }
+}
+
+/// finds the item for a role among `items`, where `fits` returns `None` for items which cannot
+/// fill the role, and whether the item is named after it otherwise
+///
+/// the named candidate wins; failing that, a sole candidate is taken
+pub fn position_by_role<T, F>(items: &[T], fits: F) -> Option<usize>
+where
+    F: Fn(&T) -> Option<bool>,
+{
+    let candidates: Vec<(usize, bool)> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(pos, item)| fits(item).map(|named| (pos, named)))
+        .collect();
+
+    match candidates[..] {
+        [(pos, _)] => Some(pos),
+        _ => candidates
+            .iter()
+            .find(|(_, named)| *named)
+            .map(|(pos, _)| *pos),
+    }
+}
+
+/// checks whether `a` and `b` consist of the same tokens
+pub fn tokens_eq<A: ToTokens, B: ToTokens>(a: &A, b: &B) -> bool {
+    a.to_token_stream().to_string() == b.to_token_stream().to_string()
+}
+
+/// replaces each `Self` in `item` by `ty`, for copying `item` into an impl whose `Self` differs
+///
+/// a `Self` starting a path, as in `Self::Output`, is replaced by the qualified `<ty>`; if the
+/// replaced tokens fail to parse, `item` is returned unchanged
+pub fn resolve_self<T: Parse + ToTokens + Clone>(item: &T, ty: &Type) -> T {
+    substitute(item, "Self", ty).unwrap_or_else(|_| item.clone())
+}
+
+/// replaces each identifier `name` in `item` by `ty`, qualifying `ty` where it starts a path
+pub fn substitute<T: Parse + ToTokens>(item: &T, name: &str, ty: &Type) -> syn::Result<T> {
+    syn::parse2(replace_ident(item.to_token_stream(), name, ty))
+}
+
+/// the identifier standing in for a type in `ty`, which is `T` for any of `T`, `&T`, `&mut T`
+pub fn placeholder(ty: &Type) -> Option<&Ident> {
+    match ty {
+        Type::Reference(ref_ty) => placeholder(&ref_ty.elem),
+        Type::Paren(paren_ty) => placeholder(&paren_ty.elem),
+        Type::Path(path_ty) if path_ty.qself.is_none() => path_ty.path.get_ident(),
+        _ => None,
+    }
+}
+
+fn replace_ident(tokens: TokenStream, name: &str, ty: &Type) -> TokenStream {
+    let mut tokens = tokens.into_iter().peekable();
+    let mut replaced = TokenStream::new();
+
+    while let Some(tt) = tokens.next() {
+        match tt {
+            TokenTree::Ident(ident) if ident == name => {
+                let starts_path = matches!(
+                    tokens.peek(),
+                    Some(TokenTree::Punct(punct))
+                        if punct.as_char() == ':' && punct.spacing() == Spacing::Joint
+                );
+                if starts_path {
+                    replaced.extend(quote!(<#ty>));
+                } else {
+                    ty.to_tokens(&mut replaced);
+                }
+            }
+            TokenTree::Group(group) => {
+                let mut new_group =
+                    Group::new(group.delimiter(), replace_ident(group.stream(), name, ty));
+                new_group.set_span(group.span());
+                replaced.extend(Some(TokenTree::Group(new_group)));
+            }
+            tt => replaced.extend(Some(tt)),
+        }
+    }
+
+    replaced
+}
+
+/// Collects the arguments of all the attributes `#[name(...)]` among `attrs`.
+pub fn attr_items(attrs: &[Attribute], name: &str) -> darling::Result<Vec<NestedMeta>> {
+    let mut items = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident(name)) {
+        match attr.parse_meta()? {
+            Meta::List(list) => items.extend(list.nested),
+            meta => {
+                let message = format!("expected `#[{}(...)]`", name);
+                return Err(darling::Error::custom(message).with_span(&meta));
+            }
+        }
+    }
+    Ok(items)
+}
+
+/// parses the name of an operator method, explaining that it takes no generic parameters
+pub fn parse_fn_ident(input: ParseStream) -> syn::Result<Ident> {
+    let ident = input.parse()?;
+    if input.peek(Token![<]) {
+        return Err(input.error(
+            "operator methods take no generic parameters; declare them on the impl instead",
+        ));
+    }
+    Ok(ident)
+}
+
+/// prints `item` as it would be written by hand, e.g. `Vec2<&T>` rather than `Vec2 < & T >`
+pub fn pretty<T: ToTokens>(item: &T) -> String {
+    let mut printed = item.to_token_stream().to_string();
+    for (spaced, tight) in [
+        (" <", "<"),
+        ("< ", "<"),
+        (" >", ">"),
+        (" ::", "::"),
+        (":: ", "::"),
+        (" ,", ","),
+        ("& ", "&"),
+        ("( ", "("),
+        (" )", ")"),
+        ("[ ", "["),
+        (" ]", "]"),
+    ] {
+        printed = printed.replace(spaced, tight);
+    }
+    printed
+}
+
+/// checks whether `item` mentions any references or lifetimes
+pub fn mentions_borrows<T: ToTokens>(item: &T) -> bool {
+    fn mentions(tokens: TokenStream) -> bool {
+        tokens.into_iter().any(|tt| match tt {
+            TokenTree::Punct(punct) => punct.as_char() == '&' || punct.as_char() == '\'',
+            TokenTree::Group(group) => mentions(group.stream()),
+            _ => false,
+        })
+    }
+
+    mentions(item.to_token_stream())
+}
+
+/// checks whether `tokens` mention the generic parameter `param`, e.g., `'a` in `&'a T`
+pub fn mentions_param(tokens: TokenStream, param: &GenericParam) -> bool {
+    match param {
+        GenericParam::Lifetime(def) => mentions_lifetime(tokens, &def.lifetime.ident),
+        GenericParam::Type(param) => mentions_any(tokens, &[&param.ident]),
+        GenericParam::Const(param) => mentions_any(tokens, &[&param.ident]),
+    }
+}
+
+/// checks whether `tokens` mention the lifetime `'ident`
+pub fn mentions_lifetime(tokens: TokenStream, ident: &Ident) -> bool {
+    count_lifetime(tokens, ident) > 0
+}
+
+/// counts the mentions of the lifetime `'ident` in `tokens`
+fn count_lifetime(tokens: TokenStream, ident: &Ident) -> usize {
+    let mut after_apostrophe = false;
+    tokens
+        .into_iter()
+        .map(|tt| {
+            let count = match &tt {
+                TokenTree::Ident(name) => (after_apostrophe && name == ident) as usize,
+                TokenTree::Group(group) => count_lifetime(group.stream(), ident),
+                _ => 0,
+            };
+            after_apostrophe = matches!(&tt, TokenTree::Punct(punct) if punct.as_char() == '\'');
+            count
+        })
+        .sum()
+}
+
+fn mentions_any(tokens: TokenStream, idents: &[&Ident]) -> bool {
+    tokens.into_iter().any(|tt| match tt {
+        TokenTree::Ident(ident) => idents.contains(&&ident),
+        TokenTree::Group(group) => mentions_any(group.stream(), idents),
+        _ => false,
+    })
+}
+
 pub trait TypeConversion: Sized {
     fn as_ref(&self) -> Self;
-    fn as_verbatim(&self) -> Self;
     fn as_deref(&self) -> Option<Self>;
-    fn try_deref(&self) -> Result<Self, String>;
+    fn is_mut_ref(&self) -> bool;
 }
 
 impl TypeConversion for Type {
@@ -30,17 +474,8 @@ impl TypeConversion for Type {
         }
     }
 
-    fn try_deref(&self) -> Result<Self, String> {
-        if let Type::Reference(ref_ty) = self {
-            let ty = &ref_ty.elem;
-            Ok(parse_quote!(#ty))
-        } else {
-            Err(format!("could not dereference type `{}`", quote!(self)))
-        }
-    }
-
-    fn as_verbatim(&self) -> Self {
-        Type::Verbatim(quote!(#self))
+    fn is_mut_ref(&self) -> bool {
+        matches!(self, Type::Reference(ref_ty) if ref_ty.mutability.is_some())
     }
 }
 
@@ -8,23 +8,134 @@ use xops_core::*;
 ///
 /// The `binop` attribute can be applied to any binary operation trait implementation (see [`xops_core::BinOpImpl`].
 /// 
-/// It has three possible arguments:
+/// It has twenty-two possible arguments:
 /// 
 /// - **`commute`**
 ///     - from `A op B`, derive `B op A`
 ///     - derives an implementation with the argument types swapped.
+///     - nothing is derived when `A` and `B` are the same type; an error is raised when `B` is a
+///       type parameter, as `impl<T> Mul<Wrapper<T>> for T` breaks the orphan rule. The same
+///       holds for `anticommute` and `commute_with`.
+///     - rejected for `Sub`, `Div`, `Rem`, `Shl`, and `Shr`, which are not commutative; use
+///       `anticommute` or `commute_with` there, or `commute(unchecked)` if `B op A` really is
+///       `A op B`.
+/// 
+/// - **`anticommute`**
+///     - from `A op B`, derive `B op A` as `-(A op B)`
+///     - for anti-commutative operations such as `Sub`; requires the output type `C` to satisfy
+///       `C: Neg<Output = C>`.
+/// 
+/// - **`commute_with = "path::to::fn"`**
+///     - from `A op B`, derive `B op A` by calling the given `fn(B, A) -> C`
+///     - for operations whose swapped form is neither of the above, e.g., `scalar / vector`.
 /// 
 /// - **`refs_clone`** 
 ///     - from `A op B`, derive `&A op B`, `A op &B`, `&A op &B`
-///     - derives implementations for reference types using cloning.
+///     - derives implementations for reference types using cloning; adds `Clone` bounds for
+///       operand types involving generic parameters.
+/// 
+/// - **`refs_copy`** 
+///     - from `A op B`, derive `&A op B`, `A op &B`, `&A op &B`
+///     - derives implementations for reference types by dereferencing `Copy` operands; adds
+///       `Copy` bounds for operand types involving generic parameters.
+///     - not to be combined with `refs_clone`, which derives the same forms, unless `derefs` or
+///       `all_refs` is given as well.
 /// 
 /// - **`derefs`**
 ///     - from `&A op &B`, derive `A op &B`, `&A op B`, `A op B`
 ///     - derives implementations for owned types.
+///     - generic parameters which only the removed references used are dropped; a form whose
+///       output type would still borrow through a removed reference, as `type Output = View<'a>`
+///       for `&'a A`, cannot exist and is skipped.
+/// 
+/// - **`all_refs`**
+///     - from any one of `A op B`, `&A op B`, `A op &B`, `&A op &B`, derive the other three
+///     - borrows an operand where the base takes a reference and clones it where the base takes
+///       it by value; giving both `refs_clone` and `derefs` does the same.
+///     - combined with `refs_copy`, dereferences instead of cloning.
+/// 
+/// - **`mut_refs`**
+///     - from `&A op B`, derive `&mut A op B`; likewise for the rhs and for both operands
+///     - derives an implementation for each shared reference operand taking a mutable one
+///       instead, which is reborrowed with `&*`; combine with `refs_clone` or `refs_copy` to
+///       start from an owned base.
+/// 
+/// - **`boxed`**, **`rc`**, **`arc`**, **`cow`**
+///     - from `A op B`, derive `P<A> op B` and `A op P<B>` for `P` among `Box`, `Rc`, `Arc`,
+///       and `Cow<'_, _>`
+///     - an operand the base takes by reference is reborrowed out of the pointer; one it takes by
+///       value is moved out of a `Box`, unwrapped from an `Rc` or `Arc` and cloned only if it is
+///       shared, or taken out of a `Cow` with `into_owned`.
+///     - as `Rc`, `Arc`, and `Cow` are foreign, the orphan rule only allows wrapping one operand
+///       when the other is local; forms whose other operand is a primitive type or a type
+///       parameter are skipped.
+/// 
+/// - **`assign`**
+///     - from `A op B`, derive `A op= B`, along with `A op= &B` if `A op &B` is derived
+///     - derives compound assignments for each derived implementation with an owned lhs whose
+///       output type is the lhs type.
+///     - the ten binary operations of `std::ops` get their `*Assign` traits; any other trait
+///       `Op` gets `OpAssign` with method `op_assign`, as generated by [`macro@op_trait`].
+///     - `assign` or `assign(copy)` copies out of `*self`, `assign(take)` uses
+///       `core::mem::take`, and `assign(replace = "EXPR")` uses `core::mem::replace` with the
+///       placeholder `EXPR`.
+/// 
+/// - **`sum(zero = "EXPR")`**
+///     - from `A + A` or `A + &A`, derive `Sum<A>` or `Sum<&A>` for `A`
+///     - derives a fold from the identity `EXPR` for each derived `Add` implementation of this
+///       form whose output type is `A`.
+/// 
+/// - **`product(one = "EXPR")`**
+///     - from `A * A` or `A * &A`, derive `Product<A>` or `Product<&A>` for `A`
+///     - same as `sum`, but for `Mul`.
+/// 
+/// - **`derive_sub`**
+///     - from `A + B`, derive `A - B` as `A + (-B)`
+///     - requires `B: Neg<Output = B>`; the derived `Sub` goes through every other argument
+///       except `commute`.
+/// 
+/// - **`derive_div(recip = "path::to::fn")`**
+///     - from `A * B`, derive `A / B` as `A * recip(B)`
+///     - the reciprocal is any `fn(B) -> B`; the derived `Div` goes through every other argument
+///       except `commute`.
+/// 
+/// - **`literal_output`**
+///     - copies the output type of the given implementation verbatim into derived implementations.
+///     - by default, derived implementations declare `type Output = <A as Op<B>>::Output;`, so
+///       the output type is defined in exactly one place; this falls back to the verbatim type
+///       when `A` or `B` involve references or lifetimes, which cannot be named there.
+/// 
+/// - **`rhs_from(C1, C2, ..)`**, **`lhs_from(C1, C2, ..)`**
+///     - from `A op B`, derive `A op C` (or `C op B`) for each listed `C`, converting the operand
+///       with `B::from` (or `A::from`) and delegating to the given implementation.
+///     - unlike `rhs_types`, the method body exists once; the macro user must ensure that
+///       `B: From<C>`.
+/// 
+/// - **`rhs_types(T1, T2, ..)`**, **`lhs_types(T1, T2, ..)`**
+///     - treats the type name in `B` (or `A`) as a placeholder, and implements the operation once
+///       per listed type by substituting it everywhere, the method body included; the given
+///       implementation itself is only emitted through these copies.
+///     - each copy goes through every other argument, so `commute` gives a coherent
+///       `impl Mul<Vec3> for f64` per listed scalar.
+///     - a placeholder declared as a type parameter is dropped from the generics, its bounds
+///       moving to the where clause; types other than paths are given as strings, e.g.
+///       `rhs_types(u8, "Vec<u8>")`.
+///
+/// ## Accepted implementations
+///
+/// The given implementation may hold its items in any order, with attributes such as doc comments or `#[inline]`, and may be `unsafe` or `default`. The output type is the associated type `Output`, or else the only associated type, and the operator method is the method taking `self` and one argument which is named after the trait (e.g. `add` for `Add`), or else the only such method. Any other items, such as associated constants, are copied into every derived implementation.
+///
+/// `type Output` may be left out, in which case it is inferred from the return type of the method, with `Self` replaced by the lhs type, and the method is made to return `Self::Output`.
+///
+/// Custom traits may name their output type differently (e.g. `type Scalar`), take further generic arguments after the rhs type (e.g. `Dot<B, Ctx>`), and take their operands by reference (e.g. `fn dot(&self, rhs: &B)`); derived implementations follow the same shape. Arguments which move the operands, i.e., the reference, conversion, assignment, fold, and derived-operation arguments, need a method taking both operands by value.
 ///
-/// ## Caution!
+/// Implementations of the operator traits of the standard library (see [`xops_core::catalog`]) are checked against the trait: the method must be the trait's own (e.g. `add` for `Add`), the output type must be `Output`, and unary or comparison traits are rejected. A trait path only counts as the standard one if it is bare or goes through its module, as in `Add`, `ops::Add`, or `std::ops::Add`.
 ///
-/// Avoid using `Self` in the output type as this often causes `binop` to fail. Sometimes it won't fail, but if the type-checker is giving you errors, try using more explicit types.
+/// References added by derived implementations are given fresh named lifetimes, `'lhs` and `'rhs`, rather than elided ones.
+///
+/// ## Using `Self`
+///
+/// Wherever `Self` appears in the rhs type, the output type, the where clause, or the other items, it is replaced by the lhs type of the given implementation before being copied into derived implementations, so `type Output = Self;` works with every argument. The method body is only copied by `lhs_types` and `rhs_types`, where `Self` keeps its meaning, so `Self` may be used freely there.
 /// 
 /// 
 /// # Example 1
@@ -167,6 +278,42 @@ use xops_core::*;
 ///     assert_eq!(a5, WrappedVec { inner: vec![0, 5, 10, 15, 20] });
 /// }
 /// ```
+/// 
+/// # Example 3
+/// 
+/// The following example shows `binop` deriving every ownership form from a mixed base implementation.
+/// ```
+/// use std::ops::Mul;
+/// use xops_macros::binop;
+/// 
+/// #[derive(Clone, PartialEq, Eq, Debug)]
+/// struct Poly(Vec<i32>);
+/// 
+/// #[binop(all_refs)]
+/// impl Mul<&Poly> for Poly {
+///     type Output = Poly;
+/// 
+///     fn mul(self, rhs: &Poly) -> Self::Output {
+///         let mut out = vec![0; self.0.len() + rhs.0.len() - 1];
+///         for (i, a) in self.0.iter().enumerate() {
+///             for (j, b) in rhs.0.iter().enumerate() {
+///                 out[i + j] += a * b;
+///             }
+///         }
+///         Poly(out)
+///     }
+/// }
+/// 
+/// fn main() {
+///     let p = Poly(vec![1, 1]);
+///     let p2 = Poly(vec![1, 2, 1]);
+/// 
+///     assert_eq!( p.clone() *  p.clone(), p2);
+///     assert_eq!(&p         *  p.clone(), p2);
+///     assert_eq!( p.clone() * &p        , p2);
+///     assert_eq!(&p         * &p        , p2);
+/// }
+/// ```
 #[proc_macro_attribute]
 pub fn binop(args: TokenStream, item: TokenStream) -> TokenStream {
     // print_ts("binop args", &args);
@@ -187,12 +334,335 @@ pub fn binop(args: TokenStream, item: TokenStream) -> TokenStream {
     TokenStream::from(expanded)
 }
 
+/// For deriving binary operations from a compound assignment.
+///
+/// The `assignop` attribute can be applied to any compound assignment trait implementation (see [`xops_core::AssignOpImpl`]), i.e., one of the form `impl OpAssign<B> for A`, to derive the corresponding operations returning `A`, which reuse the buffer of an owned lhs:
+/// 
+/// - from `A op= B`, derive `A op B` and `&A op B`
+/// - from `A op= &B`, derive `A op &B`, `&A op &B`, `A op B`, and `&A op B`
+/// 
+/// An owned lhs is mutated in place and returned, while a referenced lhs is cloned once, so `A` must be `Clone`. The ten `*Assign` traits of `std::ops` are paired with their counterparts in `core::ops`; any other trait `OpAssign` with method `op_assign` is paired with `Op` and `op`.
+/// 
+/// # Example
+/// 
+/// ```
+/// use std::ops::AddAssign;
+/// use xops_macros::assignop;
+/// 
+/// #[derive(Clone, PartialEq, Eq, Debug)]
+/// struct WrappedVec<T> {
+///     inner: Vec<T>,
+/// }
+/// 
+/// #[assignop]
+/// impl<T> AddAssign<&WrappedVec<T>> for WrappedVec<T>
+/// where
+///     T: Copy + AddAssign,
+/// {
+///     fn add_assign(&mut self, rhs: &WrappedVec<T>) {
+///         for (left, &right) in self.inner.iter_mut().zip(rhs.inner.iter()) {
+///             *left += right;
+///         }
+///     }
+/// }
+/// 
+/// fn main() {
+///     let a = WrappedVec::<i32> {
+///         inner: (0..5).collect(),
+///     };
+/// 
+///     let a2 = &a + &a; // ref + ref
+///     assert_eq!(a2, WrappedVec { inner: vec![0, 2, 4, 6, 8] });
+/// 
+///     let a3 = a2 + &a; // owned + ref, reusing the buffer of `a2`
+///     assert_eq!(a3, WrappedVec { inner: vec![0, 3, 6, 9, 12] });
+/// 
+///     let a4 = &a + a3; // ref + owned
+///     assert_eq!(a4, WrappedVec { inner: vec![0, 4, 8, 12, 16] });
+/// 
+///     let mut a5 = a.clone() + a4; // owned + owned
+///     assert_eq!(a5, WrappedVec { inner: vec![0, 5, 10, 15, 20] });
+/// 
+///     a5 += &a;
+///     assert_eq!(a5, WrappedVec { inner: vec![0, 6, 12, 18, 24] });
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn assignop(args: TokenStream, item: TokenStream) -> TokenStream {
+    let attr_args = parse_macro_input!(args as AttributeArgs);
+    let assignop_impl = parse_macro_input!(item as AssignOpImpl);
+
+    let assignop_args = match AssignOpArgs::from_list(&attr_args) {
+        Ok(args) => args,
+        Err(e) => {
+            return TokenStream::from(e.write_errors());
+        }
+    };
+
+    let expanded = assignop_impl.expand(assignop_args);
+
+    TokenStream::from(expanded)
+}
+
+/// For deriving extra implementations of a unary operation.
+///
+/// The `unop` attribute can be applied to any unary operation trait implementation (see [`xops_core::UnOpImpl`]), such as `Neg` or `Not`.
+/// 
+/// It has three possible arguments:
+/// 
+/// - **`refs_clone`**
+///     - from `op A`, derive `op &A`
+///     - derives an implementation for the reference type using cloning; adds a `Clone` bound
+///       if the operand type involves generic parameters.
+/// 
+/// - **`refs_copy`**
+///     - from `op A`, derive `op &A`
+///     - derives an implementation for the reference type by dereferencing a `Copy` operand.
+///     - not to be combined with `refs_clone`, which derives the same form.
+/// 
+/// - **`derefs`**
+///     - from `op &A`, derive `op A`
+///     - derives an implementation for the owned type.
+///     - not to be combined with `refs_clone` or `refs_copy`, which start from the owned type.
+/// 
+/// # Example
+/// 
+/// ```
+/// use std::ops::{Neg, Not};
+/// use xops_macros::unop;
+/// 
+/// #[derive(Clone, Copy, PartialEq, Debug)]
+/// struct Vec3(f64, f64, f64);
+/// 
+/// #[unop(refs_copy)]
+/// impl Neg for Vec3 {
+///     type Output = Vec3;
+/// 
+///     fn neg(self) -> Self::Output {
+///         Vec3(-self.0, -self.1, -self.2)
+///     }
+/// }
+/// 
+/// #[derive(PartialEq, Eq, Debug)]
+/// struct Mask(Vec<bool>);
+/// 
+/// #[unop(derefs)]
+/// impl Not for &Mask {
+///     type Output = Mask;
+/// 
+///     fn not(self) -> Self::Output {
+///         Mask(self.0.iter().map(|b| !b).collect())
+///     }
+/// }
+/// 
+/// fn main() {
+///     let v = Vec3(1.0, -2.0, 0.5);
+///     assert_eq!(-v, Vec3(-1.0, 2.0, -0.5));
+///     assert_eq!(-&v, Vec3(-1.0, 2.0, -0.5));
+/// 
+///     let mask = Mask(vec![true, false]);
+///     assert_eq!(!&mask, Mask(vec![false, true]));
+///     assert_eq!(!mask, Mask(vec![false, true]));
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn unop(args: TokenStream, item: TokenStream) -> TokenStream {
+    let attr_args = parse_macro_input!(args as AttributeArgs);
+    let unop_impl = parse_macro_input!(item as UnOpImpl);
+
+    let unop_args = match UnOpArgs::from_list(&attr_args) {
+        Ok(args) => args,
+        Err(e) => {
+            return TokenStream::from(e.write_errors());
+        }
+    };
+
+    let expanded = unop_impl.expand(unop_args);
+
+    TokenStream::from(expanded)
+}
+
+/// For defining a custom operator trait along with its companion items.
+///
+/// The `op_trait` attribute can be applied to a trait definition laid out like the binary operation traits of `std::ops` (see [`xops_core::OpTrait`]), i.e., with a method taking a receiver and an rhs. Next to the trait `Op` with method `op`, it generates:
+///
+/// - the compound assignment trait `OpAssign` with method `op_assign(&mut self, rhs)`, which [`macro@binop`] implements for `Op` under the argument `assign`.
+/// - the extension trait `OpExt` with method `op_ref(&self, rhs: &B)` operating on clones, implemented for every implementor of `Op`; only if `op` takes both operands by value.
+/// - the free function `op(a, b)`, equivalent to `a.op(b)`.
+///
+/// It has no arguments besides `dev_print`.
+///
+/// # Example
+///
+/// ```
+/// use xops_macros::{binop, op_trait};
+///
+/// #[op_trait]
+/// pub trait Dot<Rhs = Self> {
+///     type Output;
+///
+///     fn dot(self, rhs: Rhs) -> Self::Output;
+/// }
+///
+/// #[derive(Clone, Copy, PartialEq, Debug)]
+/// struct Vec2(f64, f64);
+///
+/// #[binop(assign)]
+/// impl Dot<f64> for Vec2 {
+///     type Output = Vec2;
+///
+///     fn dot(self, rhs: f64) -> Self::Output {
+///         Vec2(self.0 * rhs, self.1 * rhs)
+///     }
+/// }
+///
+/// #[binop]
+/// impl Dot for Vec2 {
+///     type Output = f64;
+///
+///     fn dot(self, rhs: Vec2) -> Self::Output {
+///         self.0 * rhs.0 + self.1 * rhs.1
+///     }
+/// }
+///
+/// fn main() {
+///     let (a, b) = (Vec2(1.0, 2.0), Vec2(3.0, -1.0));
+///     assert_eq!(dot(a, b), 1.0);
+///     assert_eq!(a.dot_ref(&b), 1.0);
+///
+///     let mut c = a;
+///     c.dot_assign(2.0);
+///     assert_eq!(c, Vec2(2.0, 4.0));
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn op_trait(args: TokenStream, item: TokenStream) -> TokenStream {
+    let attr_args = parse_macro_input!(args as AttributeArgs);
+    let op_trait = parse_macro_input!(item as OpTrait);
+
+    let op_trait_args = match OpTraitArgs::from_list(&attr_args) {
+        Ok(args) => args,
+        Err(e) => {
+            return TokenStream::from(e.write_errors());
+        }
+    };
+
+    let expanded = op_trait.expand(op_trait_args);
+
+    TokenStream::from(expanded)
+}
+
+/// For forwarding operators of a newtype to its field.
+///
+/// The `Ops` derive macro applies to a struct with a single field, named or not (see [`xops_core::Newtype`]), and implements each operator listed in its attributes `#[ops(...)]` by applying the operator to the field and wrapping the result:
+///
+/// - a binary operator `Op` implements `W op W`, applying `op` to both fields.
+/// - a binary operator `Op(B, ..)` implements `W op B` for each listed type `B`, applying `op` to the field and the rhs; `Self` stands for `W op W`.
+/// - a unary operator `Op` implements `op W`.
+///
+/// The traits are the ten binary and two unary operators of `std::ops`, given by name. For a generic newtype `W<T>`, the field type is bound by the operator, e.g., `T: Add<T, Output = T>`.
+///
+/// Each binary implementation is then expanded by [`macro@binop`], so all of its arguments may be listed among the operators and apply to every binary operator, e.g., `assign` or `commute`. The word `refs` derives every ownership form, as `all_refs` does for binary operators and `refs_clone` does for unary ones; combined with `refs_copy`, it dereferences instead of cloning.
+///
+/// # Example
+///
+/// ```
+/// use xops_macros::Ops;
+///
+/// #[derive(Ops, Clone, Copy, PartialEq, Debug)]
+/// #[ops(Add, Sub, Mul(f64), Neg, assign, refs, refs_copy)]
+/// struct Meters(f64);
+///
+/// fn main() {
+///     let (a, b) = (Meters(3.0), Meters(1.0));
+///     assert_eq!(a + b, Meters(4.0));
+///     assert_eq!(&a - &b, Meters(2.0));
+///     assert_eq!(a * 2.0, Meters(6.0));
+///     assert_eq!(-&a, Meters(-3.0));
+///
+///     let mut c = a;
+///     c -= &b;
+///     c *= 0.5;
+///     assert_eq!(c, Meters(1.0));
+/// }
+/// ```
+#[proc_macro_derive(Ops, attributes(ops))]
+pub fn derive_ops(item: TokenStream) -> TokenStream {
+    let newtype = parse_macro_input!(item as Newtype);
+
+    let ops_args = match OpsArgs::from_attrs(&newtype.attrs, "ops") {
+        Ok(args) => args,
+        Err(e) => {
+            return TokenStream::from(e.write_errors());
+        }
+    };
+
+    let expanded = newtype.expand(ops_args);
+
+    TokenStream::from(expanded)
+}
+
+/// For applying operators to a struct field by field.
+///
+/// The `FieldwiseOps` derive macro applies to a struct with any number of fields, named or not (see [`xops_core::Fieldwise`]), and implements each operator listed in its attributes `#[fieldwise(...)]` by applying the operator to each field:
+///
+/// - a binary operator `Op` implements `A op A`, whose result has each field computed from the matching fields of the operands.
+/// - a unary operator `Op` implements `op A`, whose result has each field computed from the field of the operand.
+///
+/// A field marked `#[fieldwise(skip)]` takes no part in the operations and is copied from the lhs instead. For a generic struct, each field type is bound by the operator, e.g., `T: Add<Output = T>`.
+///
+/// As with [`macro@Ops`], the traits are the ten binary and two unary operators of `std::ops`, given by name, and each binary implementation is expanded by [`macro@binop`], whose arguments may be listed among the operators; the word `refs` derives every ownership form.
+///
+/// # Example
+///
+/// ```
+/// use xops_macros::FieldwiseOps;
+///
+/// #[derive(FieldwiseOps, Clone, Copy, PartialEq, Debug)]
+/// #[fieldwise(Add, Sub, Mul, Neg, assign, refs_copy)]
+/// struct Rgba {
+///     r: f32,
+///     g: f32,
+///     b: f32,
+///     #[fieldwise(skip)]
+///     a: f32,
+/// }
+///
+/// fn main() {
+///     let red = Rgba { r: 1.0, g: 0.0, b: 0.0, a: 1.0 };
+///     let blue = Rgba { r: 0.0, g: 0.0, b: 1.0, a: 0.5 };
+///     assert_eq!(red + blue, Rgba { r: 1.0, g: 0.0, b: 1.0, a: 1.0 });
+///     assert_eq!(&blue - &red, Rgba { r: -1.0, g: 0.0, b: 1.0, a: 0.5 });
+///     assert_eq!(-red, Rgba { r: -1.0, g: -0.0, b: -0.0, a: 1.0 });
+///
+///     let mut purple = red;
+///     purple += blue;
+///     purple *= Rgba { r: 0.5, g: 1.0, b: 0.5, a: 0.0 };
+///     assert_eq!(purple, Rgba { r: 0.5, g: 0.0, b: 0.5, a: 1.0 });
+/// }
+/// ```
+#[proc_macro_derive(FieldwiseOps, attributes(fieldwise))]
+pub fn derive_fieldwise_ops(item: TokenStream) -> TokenStream {
+    let fieldwise = parse_macro_input!(item as Fieldwise);
+
+    let ops_args = match OpsArgs::from_attrs(&fieldwise.attrs, "fieldwise") {
+        Ok(args) => args,
+        Err(e) => {
+            return TokenStream::from(e.write_errors());
+        }
+    };
+
+    let expanded = fieldwise.expand(ops_args);
+
+    TokenStream::from(expanded)
+}
+
 // testing -----------------------------------------------------------------------------------------
 
 
 
 fn print_ts(header: &str, item: &TokenStream) {
-    println!("BEGIN {} \n{}\nEND\n", header, item.to_string());
+    println!("BEGIN {} \n{}\nEND\n", header, item);
 }
 
 #[proc_macro_attribute]